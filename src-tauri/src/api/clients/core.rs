@@ -0,0 +1,668 @@
+use crate::api::servers::args::InputArg;
+use crate::error::{MagnetError, MagnetResult};
+use crate::utils::fs::write_atomic;
+use crate::utils::jsonc::{self, Segment};
+use crate::utils::os::{get_config_dir, get_home, get_magnet_dir};
+use crate::APP_STATE_FILENAME;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri_plugin_store::StoreExt;
+
+/// Number of config backups kept per client; older ones are pruned.
+const MAX_CONFIG_BACKUPS: usize = 10;
+
+/// MCP hosts whose configuration file Magnet knows how to read and write.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum McpClient {
+    #[default]
+    ClaudeDesktop,
+    Cursor,
+    Windsurf,
+    Zed,
+    VsCode,
+    Continue,
+    Cline,
+}
+
+/// How a client lays out its MCP servers inside its configuration file.
+enum ClientLayout {
+    /// `{"mcpServers": {"<id>": {"command", "args", "env"}}}`
+    McpServers,
+    /// `{"servers": {"<id>": {"type": "stdio", "command", "args", "env"}}}`
+    VsCode,
+    /// `{"context_servers": {"<id>": {"command": {"path", "args", "env"}}}}`
+    Zed,
+    /// `{"experimental": {"modelContextProtocolServers": [{"name", "transport"}]}}`
+    Continue,
+}
+
+impl McpClient {
+    pub const ALL: [McpClient; 7] = [
+        McpClient::ClaudeDesktop,
+        McpClient::Cursor,
+        McpClient::Windsurf,
+        McpClient::Zed,
+        McpClient::VsCode,
+        McpClient::Continue,
+        McpClient::Cline,
+    ];
+
+    /// Stable identifier used for on-disk names such as the backup directory.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            McpClient::ClaudeDesktop => "claude-desktop",
+            McpClient::Cursor => "cursor",
+            McpClient::Windsurf => "windsurf",
+            McpClient::Zed => "zed",
+            McpClient::VsCode => "vscode",
+            McpClient::Continue => "continue",
+            McpClient::Cline => "cline",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            McpClient::ClaudeDesktop => "Claude Desktop",
+            McpClient::Cursor => "Cursor",
+            McpClient::Windsurf => "Windsurf",
+            McpClient::Zed => "Zed",
+            McpClient::VsCode => "VS Code",
+            McpClient::Continue => "Continue",
+            McpClient::Cline => "Cline",
+        }
+    }
+
+    pub fn config_path(&self) -> MagnetResult<PathBuf> {
+        let home = get_home()?;
+        Ok(match self {
+            McpClient::ClaudeDesktop => get_config_dir()?
+                .join("Claude")
+                .join("claude_desktop_config.json"),
+            McpClient::Cursor => home.join(".cursor").join("mcp.json"),
+            McpClient::Windsurf => home
+                .join(".codeium")
+                .join("windsurf")
+                .join("mcp_config.json"),
+            McpClient::Zed => {
+                #[cfg(any(target_os = "macos", target_os = "linux"))]
+                {
+                    home.join(".config").join("zed").join("settings.json")
+                }
+                #[cfg(target_os = "windows")]
+                {
+                    get_config_dir()?.join("Zed").join("settings.json")
+                }
+            }
+            McpClient::VsCode => vscode_user_dir()?.join("mcp.json"),
+            McpClient::Continue => home.join(".continue").join("config.json"),
+            McpClient::Cline => vscode_user_dir()?
+                .join("globalStorage")
+                .join("saoudrizwan.claude-dev")
+                .join("settings")
+                .join("cline_mcp_settings.json"),
+        })
+    }
+
+    /// A client counts as present when its config file, or the directory that
+    /// would hold it, already exists.
+    pub fn is_detected(&self) -> bool {
+        let Ok(config_path) = self.config_path() else {
+            return false;
+        };
+        config_path.exists() || config_path.parent().is_some_and(|dir| dir.is_dir())
+    }
+
+    fn layout(&self) -> ClientLayout {
+        match self {
+            McpClient::ClaudeDesktop
+            | McpClient::Cursor
+            | McpClient::Windsurf
+            | McpClient::Cline => ClientLayout::McpServers,
+            McpClient::VsCode => ClientLayout::VsCode,
+            McpClient::Zed => ClientLayout::Zed,
+            McpClient::Continue => ClientLayout::Continue,
+        }
+    }
+}
+
+fn vscode_user_dir() -> MagnetResult<PathBuf> {
+    Ok(get_config_dir()?.join("Code").join("User"))
+}
+
+/// Clients that installs target when the caller does not name any.
+pub fn target_clients(app_handle: &tauri::AppHandle) -> MagnetResult<Vec<McpClient>> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let clients: Vec<McpClient> = store
+        .get("target_clients")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    if clients.is_empty() {
+        Ok(vec![McpClient::default()])
+    } else {
+        Ok(clients)
+    }
+}
+
+pub fn set_target_clients(
+    app_handle: &tauri::AppHandle,
+    clients: &[McpClient],
+) -> MagnetResult<()> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    store.set("target_clients", json!(clients));
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientServerConfig {
+    #[serde(default)]
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(default, with = "raw_string_map")]
+    pub env: HashMap<String, String>,
+    /// Magnet's bookkeeping below is kept in its store, see
+    /// [`ServerMetadata`]. It is only read from client configs written by
+    /// older versions.
+    #[serde(rename = "commandCreator", default, skip_serializing)]
+    pub command_creator: String,
+    #[serde(rename = "inputArg", default, skip_serializing)]
+    pub input_arg: InputArg,
+    #[serde(rename = "inputArgs", default, skip_serializing)]
+    pub input_args: Vec<InputArg>,
+}
+
+impl ClientServerConfig {
+    pub fn metadata(&self) -> ServerMetadata {
+        ServerMetadata {
+            command_creator: self.command_creator.clone(),
+            input_args: self.input_args.clone(),
+        }
+    }
+
+    fn apply_metadata(&mut self, metadata: ServerMetadata) {
+        self.command_creator = metadata.command_creator;
+        self.input_args = metadata.input_args;
+    }
+}
+
+/// What Magnet remembers about a server it wrote into a client's config. It
+/// lives in Magnet's store, by client and server id, since the config file
+/// belongs to the host and hosts such as Zed reject unknown keys.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServerMetadata {
+    #[serde(rename = "commandCreator", default)]
+    command_creator: String,
+    #[serde(rename = "inputArgs", default)]
+    input_args: Vec<InputArg>,
+}
+
+type MetadataByClient = HashMap<String, HashMap<String, ServerMetadata>>;
+
+fn load_metadata(app_handle: &tauri::AppHandle) -> MagnetResult<MetadataByClient> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    Ok(store
+        .get("client_servers")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default())
+}
+
+/// Adds Magnet's bookkeeping for `client` to `servers` read from its config.
+pub fn with_metadata(
+    app_handle: &tauri::AppHandle,
+    client: McpClient,
+    mut servers: HashMap<String, ClientServerConfig>,
+) -> MagnetResult<HashMap<String, ClientServerConfig>> {
+    let mut metadata = load_metadata(app_handle)?
+        .remove(client.slug())
+        .unwrap_or_default();
+    for (id, server_config) in &mut servers {
+        if let Some(metadata) = metadata.remove(id) {
+            server_config.apply_metadata(metadata);
+        }
+    }
+    Ok(servers)
+}
+
+/// Records Magnet's bookkeeping for a server in `client`, or forgets it when
+/// `metadata` is `None`.
+pub fn set_server_metadata(
+    app_handle: &tauri::AppHandle,
+    client: McpClient,
+    server_id: &str,
+    metadata: Option<ServerMetadata>,
+) -> MagnetResult<()> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let mut all = load_metadata(app_handle)?;
+    let servers = all.entry(client.slug().to_string()).or_default();
+    match metadata {
+        Some(metadata) => servers.insert(server_id.to_string(), metadata),
+        None => servers.remove(server_id),
+    };
+    if servers.is_empty() {
+        all.remove(client.slug());
+    }
+    store.set("client_servers", json!(all));
+    Ok(())
+}
+
+mod raw_string_map {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(map.iter().map(|(k, v)| (k, v.replace("\\n", "\n"))))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HashMap::deserialize(deserializer)
+    }
+}
+
+/// A client's configuration file. Only the MCP server entries are interpreted
+/// and edited in place; comments and everything else in the document are
+/// written back untouched.
+#[derive(Debug)]
+pub struct ClientConfig {
+    client: McpClient,
+    mcp_servers: HashMap<String, ClientServerConfig>,
+    text: String,
+}
+
+impl ClientConfig {
+    pub fn load(client: McpClient) -> MagnetResult<Self> {
+        let config_path = client.config_path()?;
+        let text = match std::fs::read_to_string(&config_path) {
+            Ok(content) if !content.trim().is_empty() => content,
+            _ => {
+                debug!("Config file not found, returning empty HashMap");
+                return Ok(ClientConfig {
+                    client,
+                    mcp_servers: HashMap::new(),
+                    text: "{}\n".to_string(),
+                });
+            }
+        };
+        debug!("ClientConfig loaded config");
+        let document = jsonc::parse(&text).map_err(|e| MagnetError::ConfigParse {
+            path: config_path.to_string_lossy().to_string(),
+            message: e.to_string(),
+        })?;
+        let mcp_servers = Self::read_servers(&client, &document);
+        debug!("ClientConfig parsed config");
+        Ok(ClientConfig {
+            client,
+            mcp_servers,
+            text,
+        })
+    }
+
+    pub fn save(&self) -> MagnetResult<()> {
+        let config_path = self.client.config_path()?;
+        backup_config(self.client)?;
+        write_atomic(&config_path, self.text.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn servers(&self) -> &HashMap<String, ClientServerConfig> {
+        &self.mcp_servers
+    }
+
+    pub fn into_servers(self) -> HashMap<String, ClientServerConfig> {
+        self.mcp_servers
+    }
+
+    /// Path of the server's entry in the document.
+    fn entry_path<'a>(&self, server_id: &'a str) -> Vec<Segment<'a>> {
+        match self.client.layout() {
+            ClientLayout::McpServers => vec![Segment::Key("mcpServers"), Segment::Key(server_id)],
+            ClientLayout::VsCode => vec![Segment::Key("servers"), Segment::Key(server_id)],
+            ClientLayout::Zed => vec![Segment::Key("context_servers"), Segment::Key(server_id)],
+            ClientLayout::Continue => vec![
+                Segment::Key("experimental"),
+                Segment::Key("modelContextProtocolServers"),
+                Segment::Where("name", server_id),
+            ],
+        }
+    }
+
+    /// Writes `entry` at the server's place in the document, or removes the
+    /// server when it is `None`.
+    fn set_entry(&mut self, server_id: &str, entry: Option<Value>) -> Result<()> {
+        let path = self.entry_path(server_id);
+        self.text = jsonc::set(&self.text, &path, entry.as_ref())?;
+        Ok(())
+    }
+
+    /// Changes the executable an installed server is started with, keeping
+    /// the rest of its entry as it is.
+    pub fn set_command(&mut self, server_id: &str, command: &str) -> Result<()> {
+        let Some(server_config) = self.mcp_servers.get_mut(server_id) else {
+            return Err(anyhow!("{} is not installed", server_id));
+        };
+        server_config.command = command.to_string();
+        let mut path = self.entry_path(server_id);
+        match self.client.layout() {
+            ClientLayout::McpServers | ClientLayout::VsCode => path.push(Segment::Key("command")),
+            ClientLayout::Zed => {
+                let document = jsonc::parse(&self.text)?;
+                path.push(Segment::Key("command"));
+                // Older Zed entries keep the command at the top level.
+                if document["context_servers"][server_id]["command"].is_object() {
+                    path.push(Segment::Key("path"));
+                }
+            }
+            ClientLayout::Continue => {
+                path.extend([Segment::Key("transport"), Segment::Key("command")])
+            }
+        }
+        self.text = jsonc::set(&self.text, &path, Some(&json!(command)))?;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, server_id: &str, server_config: ClientServerConfig) -> Result<()> {
+        let entry = serde_json::to_value(&server_config)?;
+        let entry = match self.client.layout() {
+            ClientLayout::McpServers => entry,
+            ClientLayout::VsCode => {
+                let mut entry = entry;
+                entry["type"] = json!("stdio");
+                entry
+            }
+            // Zed rejects unknown keys here.
+            ClientLayout::Zed => json!({
+                "command": {
+                    "path": entry["command"],
+                    "args": entry["args"],
+                    "env": entry["env"],
+                },
+                "settings": {},
+            }),
+            ClientLayout::Continue => json!({
+                "name": server_id,
+                "transport": {
+                    "type": "stdio",
+                    "command": entry["command"],
+                    "args": entry["args"],
+                    "env": entry["env"],
+                },
+            }),
+        };
+        self.set_entry(server_id, Some(entry))?;
+        self.mcp_servers
+            .insert(server_id.to_string(), server_config);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, server_id: &str) -> Result<Option<ClientServerConfig>> {
+        let Some(removed) = self.mcp_servers.remove(server_id) else {
+            return Ok(None);
+        };
+        self.set_entry(server_id, None)?;
+        Ok(Some(removed))
+    }
+
+    fn read_servers(client: &McpClient, document: &Value) -> HashMap<String, ClientServerConfig> {
+        let entries: Vec<(String, Value)> = match client.layout() {
+            ClientLayout::McpServers => object_entries(&document["mcpServers"]),
+            ClientLayout::VsCode => object_entries(&document["servers"])
+                .into_iter()
+                .filter(|(_, entry)| matches!(entry["type"].as_str(), None | Some("stdio")))
+                .collect(),
+            ClientLayout::Zed => object_entries(&document["context_servers"])
+                .into_iter()
+                .map(|(id, entry)| {
+                    if let Some(command) = entry["command"].as_object() {
+                        let entry = json!({
+                            "command": command.get("path").cloned().unwrap_or(Value::Null),
+                            "args": command.get("args").cloned().unwrap_or(json!([])),
+                            "env": command.get("env").cloned().unwrap_or(json!({})),
+                        });
+                        return (id, entry);
+                    }
+                    (id, entry)
+                })
+                .collect(),
+            ClientLayout::Continue => document["experimental"]["modelContextProtocolServers"]
+                .as_array()
+                .map(|servers| {
+                    servers
+                        .iter()
+                        .filter_map(|server| {
+                            let name = server["name"].as_str()?;
+                            let mut entry = server["transport"].clone();
+                            entry["commandCreator"] = server["commandCreator"].clone();
+                            entry["inputArg"] = server["inputArg"].clone();
+                            Some((name.to_string(), entry))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        entries
+            .into_iter()
+            .filter_map(|(id, mut entry)| {
+                if let Value::Object(fields) = &mut entry {
+                    fields.retain(|_, value| !value.is_null());
+                }
+                match serde_json::from_value::<ClientServerConfig>(entry) {
+                    Ok(server_config) => Some((id, server_config)),
+                    Err(e) => {
+                        debug!("Skipping server {} that Magnet cannot manage: {}", id, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigBackup {
+    id: String,
+    client: McpClient,
+    #[serde(rename = "createdAt")]
+    created_at: u64,
+    size: u64,
+}
+
+fn backup_dir(client: McpClient) -> Result<PathBuf> {
+    Ok(get_magnet_dir()?.join("backups").join(client.slug()))
+}
+
+/// Copies the client's current config file into its backup directory and
+/// prunes the oldest backups beyond `MAX_CONFIG_BACKUPS`. Nothing is written
+/// when there is no config yet or it matches the most recent backup.
+pub fn backup_config(client: McpClient) -> Result<Option<ConfigBackup>> {
+    let current = match fs::read(client.config_path()?) {
+        Ok(content) if !content.is_empty() => content,
+        _ => return Ok(None),
+    };
+    let backups = list_config_backups(client)?;
+    if let Some(latest) = backups.first() {
+        let latest_path = backup_dir(client)?.join(format!("{}.json", latest.id));
+        if fs::read(latest_path).ok().as_deref() == Some(current.as_slice()) {
+            trace!("Config for {} unchanged since last backup", client.slug());
+            return Ok(None);
+        }
+    }
+
+    let dir = backup_dir(client)?;
+    fs::create_dir_all(&dir)?;
+    let mut created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    while dir.join(format!("{}.json", created_at)).exists() {
+        created_at += 1;
+    }
+    let backup_path = dir.join(format!("{}.json", created_at));
+    write_atomic(&backup_path, &current)?;
+    trace!(
+        "Backed up {} config to {}",
+        client.slug(),
+        backup_path.to_string_lossy()
+    );
+
+    for stale in list_config_backups(client)?.iter().skip(MAX_CONFIG_BACKUPS) {
+        fs::remove_file(dir.join(format!("{}.json", stale.id)))?;
+    }
+
+    Ok(Some(ConfigBackup {
+        id: created_at.to_string(),
+        client,
+        created_at,
+        size: current.len() as u64,
+    }))
+}
+
+/// Backups of the client's config, newest first.
+pub fn list_config_backups(client: McpClient) -> Result<Vec<ConfigBackup>> {
+    let dir = backup_dir(client)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(vec![]),
+    };
+    let mut backups: Vec<ConfigBackup> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            let created_at: u64 = path.file_stem()?.to_str()?.parse().ok()?;
+            Some(ConfigBackup {
+                id: created_at.to_string(),
+                client,
+                created_at,
+                size: entry.metadata().ok()?.len(),
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Puts a backup back in place of the client's config. The config being
+/// replaced is backed up first, so a restore can itself be undone.
+pub fn restore_config_backup(client: McpClient, backup_id: &str) -> Result<()> {
+    if backup_id.is_empty() || !backup_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid backup id: {}", backup_id));
+    }
+    let backup_path = backup_dir(client)?.join(format!("{}.json", backup_id));
+    let content = fs::read(&backup_path).with_context(|| {
+        format!(
+            "Backup {} not found for {}",
+            backup_id,
+            client.display_name()
+        )
+    })?;
+    std::str::from_utf8(&content)
+        .ok()
+        .and_then(|content| jsonc::parse(content).ok())
+        .with_context(|| format!("Backup {} is not valid JSON", backup_id))?;
+
+    backup_config(client)?;
+    write_atomic(&client.config_path()?, &content)?;
+    debug!(
+        "Restored {} config from backup {}",
+        client.slug(),
+        backup_id
+    );
+    Ok(())
+}
+
+fn object_entries(value: &Value) -> Vec<(String, Value)> {
+    value
+        .as_object()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|(id, entry)| (id.clone(), entry.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(client: McpClient, text: &str) -> ClientConfig {
+        ClientConfig {
+            client,
+            mcp_servers: ClientConfig::read_servers(&client, &jsonc::parse(text).unwrap()),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn set_command_keeps_the_rest_of_the_entry() {
+        let mut claude = config(
+            McpClient::ClaudeDesktop,
+            r#"{
+  // Managed by Magnet
+  "mcpServers": {
+    "files": { "command": "/old/magnet", "args": ["--mcp-launch", "files"], "disabled": true }
+  }
+}
+"#,
+        );
+        claude.set_command("files", "/new/magnet").unwrap();
+        assert!(claude.text.contains("// Managed by Magnet"));
+        let document = jsonc::parse(&claude.text).unwrap();
+        assert_eq!(document["mcpServers"]["files"]["command"], "/new/magnet");
+        assert_eq!(document["mcpServers"]["files"]["disabled"], true);
+        assert_eq!(claude.servers()["files"].command, "/new/magnet");
+        assert!(claude.set_command("missing", "/new/magnet").is_err());
+    }
+
+    #[test]
+    fn set_command_follows_client_layouts() {
+        let mut zed = config(
+            McpClient::Zed,
+            r#"{
+  "context_servers": {
+    "files": { "command": { "path": "/old/magnet", "args": [] }, "settings": {} },
+    "legacy": { "command": "/old/magnet", "args": [] }
+  }
+}"#,
+        );
+        zed.set_command("files", "/new/magnet").unwrap();
+        zed.set_command("legacy", "/new/magnet").unwrap();
+        let document = jsonc::parse(&zed.text).unwrap();
+        assert_eq!(
+            document["context_servers"]["files"]["command"]["path"],
+            "/new/magnet"
+        );
+        assert_eq!(
+            document["context_servers"]["legacy"]["command"],
+            "/new/magnet"
+        );
+
+        let mut continue_config = config(
+            McpClient::Continue,
+            r#"{
+  "experimental": {
+    "modelContextProtocolServers": [
+      { "name": "other", "transport": { "type": "stdio", "command": "npx", "args": [] } },
+      { "name": "files", "transport": { "type": "stdio", "command": "/old/magnet", "args": [] } }
+    ]
+  }
+}"#,
+        );
+        continue_config.set_command("files", "/new/magnet").unwrap();
+        let document = jsonc::parse(&continue_config.text).unwrap();
+        let servers = &document["experimental"]["modelContextProtocolServers"];
+        assert_eq!(servers[0]["transport"]["command"], "npx");
+        assert_eq!(servers[1]["transport"]["command"], "/new/magnet");
+    }
+}
@@ -0,0 +1,56 @@
+use super::core::{
+    list_config_backups as list_backups, restore_config_backup as restore_backup,
+    set_target_clients as store_target_clients, target_clients, ConfigBackup, McpClient,
+};
+use crate::error::MagnetResult;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ClientInfo {
+    client: McpClient,
+    name: &'static str,
+    #[serde(rename = "configPath")]
+    config_path: String,
+    detected: bool,
+    #[serde(rename = "isTarget")]
+    is_target: bool,
+}
+
+#[tauri::command]
+pub async fn get_clients(app_handle: tauri::AppHandle) -> MagnetResult<Vec<ClientInfo>> {
+    let targets = target_clients(&app_handle)?;
+    Ok(McpClient::ALL
+        .iter()
+        .map(|client| ClientInfo {
+            client: *client,
+            name: client.display_name(),
+            config_path: client
+                .config_path()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            detected: client.is_detected(),
+            is_target: targets.contains(client),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn set_target_clients(
+    app_handle: tauri::AppHandle,
+    clients: Vec<McpClient>,
+) -> MagnetResult<()> {
+    store_target_clients(&app_handle, &clients)
+}
+
+#[tauri::command]
+pub async fn list_config_backups(client: Option<McpClient>) -> MagnetResult<Vec<ConfigBackup>> {
+    Ok(list_backups(client.unwrap_or_default())?)
+}
+
+#[tauri::command]
+pub async fn restore_config_backup(
+    client: Option<McpClient>,
+    backup_id: String,
+) -> MagnetResult<()> {
+    Ok(restore_backup(client.unwrap_or_default(), &backup_id)?)
+}
@@ -0,0 +1,85 @@
+use super::resolver::find_executables;
+use crate::APP_STATE_FILENAME;
+use anyhow::Result;
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri_plugin_store::StoreExt;
+use tokio::process::Command;
+
+pub struct ContainerHandler;
+
+/// Engines tried in order; both accept the same `run` flags.
+const CONTAINER_ENGINES: [&str; 2] = ["docker", "podman"];
+/// How long `<engine> --version` may take before the engine counts as broken.
+const ENGINE_PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The container engine servers declaring an image are launched with.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContainerEngine {
+    name: String,
+    path: String,
+    version: String,
+}
+
+impl ContainerEngine {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+async fn probe(name: &str, path: &Path) -> Option<ContainerEngine> {
+    let output = tokio::time::timeout(
+        ENGINE_PROBE_TIMEOUT,
+        Command::new(path)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        trace!("{} --version failed: {}", name, output.status);
+        return None;
+    }
+    Some(ContainerEngine {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        version: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    })
+}
+
+impl ContainerHandler {
+    /// Finds a working docker or podman, preferring the one found last time,
+    /// and records it in the store. Returns `None` when neither runs.
+    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<Option<ContainerEngine>> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        let stored: Option<ContainerEngine> = store
+            .get("container_engine")
+            .and_then(|v| serde_json::from_value(v).ok());
+
+        let mut candidates: Vec<(String, PathBuf)> = vec![];
+        if let Some(stored) = stored {
+            candidates.push((stored.name, PathBuf::from(stored.path)));
+        }
+        for name in CONTAINER_ENGINES {
+            if let Some(path) = find_executables(name).await.into_iter().next() {
+                candidates.push((name.to_string(), path));
+            }
+        }
+
+        for (name, path) in candidates {
+            if let Some(engine) = probe(&name, &path).await {
+                trace!("Using container engine {:?}", engine);
+                store.set("container_engine", serde_json::to_value(&engine)?);
+                return Ok(Some(engine));
+            }
+        }
+        store.delete("container_engine");
+        Ok(None)
+    }
+}
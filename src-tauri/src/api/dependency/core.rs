@@ -1,65 +1,746 @@
+use crate::error::MagnetError;
+use crate::utils::archive;
 use crate::APP_STATE_FILENAME;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::trace;
 use reqwest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fs;
-use std::io::Cursor;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager, Runtime};
 use tauri_plugin_store::StoreExt;
-use xshell::{cmd, Shell};
-#[cfg(target_os = "windows")]
-use zip::ZipArchive;
-#[cfg(target_os = "macos")]
-use {flate2::read::GzDecoder, tar::Archive};
 
-use crate::utils::os::{detect_shell, get_home};
+use super::resolver::{find_tool_dir, is_valid_dir, resolve};
+use crate::utils::os::get_home;
+use crate::utils::version::Version;
 
 pub struct NpmHandler;
 pub struct UVHandler;
+pub struct BunHandler;
+pub struct DenoHandler;
 
-pub struct ResourceHandler;
-const SERVERS_URL: &str = "https://file.magnetlabs.xyz/server-configuration/servers-v0.1.json";
+/// Node.js release installed when the user does not pick one.
+pub const DEFAULT_NODE_VERSION: &str = "v22.11.0";
+/// uv release installed when the user does not pick one.
+pub const DEFAULT_UV_VERSION: &str = "0.5.5";
+/// Bun release installed when the user does not pick one.
+pub const DEFAULT_BUN_VERSION: &str = "1.1.38";
+/// Deno release installed when the user does not pick one.
+pub const DEFAULT_DENO_VERSION: &str = "v2.1.4";
+const NODE_INDEX_URL: &str = "https://nodejs.org/dist/index.json";
+const UV_RELEASES_URL: &str = "https://api.github.com/repos/astral-sh/uv/releases?per_page=100";
+const BUN_RELEASES_URL: &str = "https://api.github.com/repos/oven-sh/bun/releases?per_page=100";
+const DENO_RELEASES_URL: &str = "https://api.github.com/repos/denoland/deno/releases?per_page=100";
 
-impl NpmHandler {
-    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
-        let store = app_handle.store(APP_STATE_FILENAME).unwrap();
-        let shell = Shell::new()?;
-        let shell_name = detect_shell()?;
-
-        let node_path = store
-            .get("node_path")
-            .and_then(|s| s.as_str().map(String::from))
-            .unwrap_or("".to_owned());
-        if !node_path.is_empty() {
-            if let Ok(metadata) = fs::metadata(&node_path) {
-                if metadata.is_dir() || metadata.is_symlink() || metadata.is_file() {
-                    trace!("Node path exists: {}", node_path);
-                    return Ok(true);
+/// Event carrying [`InstallProgress`] updates.
+pub const INSTALL_PROGRESS_EVENT: &str = "dependency-progress";
+/// Minimum number of downloaded bytes between two progress events.
+const PROGRESS_BYTES_STEP: u64 = 256 * 1024;
+/// Minimum number of extracted entries between two progress events.
+const PROGRESS_FILES_STEP: u64 = 100;
+
+/// A runtime Magnet can install on its own.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Dependency {
+    Node,
+    Uv,
+    Bun,
+    Deno,
+}
+
+impl Dependency {
+    pub const ALL: [Dependency; 4] = [
+        Dependency::Node,
+        Dependency::Uv,
+        Dependency::Bun,
+        Dependency::Deno,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Dependency::Node => "Node.js",
+            Dependency::Uv => "uv",
+            Dependency::Bun => "Bun",
+            Dependency::Deno => "Deno",
+        }
+    }
+
+    /// The runtime a registry `command` is launched with, for the launchers
+    /// Magnet can point at its own installs.
+    pub fn for_launcher(command: &str) -> Option<Self> {
+        match command {
+            "npx" => Some(Dependency::Node),
+            "uvx" => Some(Dependency::Uv),
+            "bunx" => Some(Dependency::Bun),
+            "deno" => Some(Dependency::Deno),
+            _ => None,
+        }
+    }
+
+    pub fn default_version(&self) -> &'static str {
+        match self {
+            Dependency::Node => DEFAULT_NODE_VERSION,
+            Dependency::Uv => DEFAULT_UV_VERSION,
+            Dependency::Bun => DEFAULT_BUN_VERSION,
+            Dependency::Deno => DEFAULT_DENO_VERSION,
+        }
+    }
+
+    /// Store key holding the directory or executable servers are launched with.
+    pub(crate) fn path_key(&self) -> &'static str {
+        match self {
+            Dependency::Node => "node_path",
+            Dependency::Uv => "uv_path",
+            Dependency::Bun => "bun_path",
+            Dependency::Deno => "deno_path",
+        }
+    }
+
+    pub(crate) fn use_system_key(&self) -> &'static str {
+        match self {
+            Dependency::Node => "use_system_node",
+            Dependency::Uv => "use_system_uv",
+            Dependency::Bun => "use_system_bun",
+            Dependency::Deno => "use_system_deno",
+        }
+    }
+
+    /// Directory Magnet unpacks its own installs of the runtime into.
+    pub(crate) fn managed_root(&self) -> Result<PathBuf> {
+        let home = get_home()?;
+        let name = match self {
+            Dependency::Node => "node",
+            Dependency::Uv => "uv",
+            Dependency::Bun => "bun",
+            Dependency::Deno => "deno",
+        };
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        let root = home.join(format!(".{}", name));
+        #[cfg(target_os = "windows")]
+        let root = home.join("AppData").join("Local").join(name);
+        Ok(root)
+    }
+
+    /// Spells a version the way the upstream project tags its releases.
+    pub(crate) fn normalize_version(&self, version: &str) -> Result<String> {
+        let parsed =
+            Version::parse(version).ok_or_else(|| anyhow!("Invalid version: {}", version))?;
+        Ok(match self {
+            Dependency::Node | Dependency::Deno => format!("v{}", parsed),
+            Dependency::Uv | Dependency::Bun => parsed.to_string(),
+        })
+    }
+}
+
+/// A release that can be installed, as listed by [`available_versions`].
+#[derive(Debug, Serialize, Clone)]
+pub struct AvailableVersion {
+    version: String,
+    /// Node.js LTS codename, e.g. `Jod`.
+    lts: Option<String>,
+    installed: bool,
+    active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyVersions {
+    dependency: Dependency,
+    /// Managed version in use, `None` when a system install is used.
+    active: Option<String>,
+    /// Newest stable release (latest LTS for Node.js).
+    latest: Option<String>,
+    /// Whether the active managed version is older than `latest`.
+    outdated: bool,
+    versions: Vec<AvailableVersion>,
+}
+
+/// Managed installs recorded in the store as version -> path.
+pub(crate) fn managed_versions(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    dependency: Dependency,
+) -> HashMap<String, String> {
+    store
+        .get("managed_versions")
+        .and_then(|v| serde_json::from_value::<HashMap<Dependency, _>>(v).ok())
+        .and_then(|mut all| all.remove(&dependency))
+        .unwrap_or_default()
+}
+
+/// Records a managed version and makes it the active one.
+pub(crate) fn activate_managed_version(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    dependency: Dependency,
+    version: &str,
+    path: String,
+) -> Result<()> {
+    let mut all: HashMap<Dependency, HashMap<String, String>> = store
+        .get("managed_versions")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    all.entry(dependency)
+        .or_default()
+        .insert(version.to_string(), path.clone());
+    store.set("managed_versions", serde_json::to_value(all)?);
+    store.set(dependency.path_key(), path);
+    store.set(dependency.use_system_key(), false);
+    Ok(())
+}
+
+/// Drops managed versions from the store. Does not touch the files.
+pub(crate) fn forget_managed_versions(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    dependency: Dependency,
+    versions: &[String],
+) -> Result<()> {
+    let mut all: HashMap<Dependency, HashMap<String, String>> = store
+        .get("managed_versions")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    if let Some(installed) = all.get_mut(&dependency) {
+        installed.retain(|version, _| !versions.contains(version));
+    }
+    store.set("managed_versions", serde_json::to_value(all)?);
+    Ok(())
+}
+
+/// The managed version currently in use, if any.
+pub(crate) fn active_managed_version(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    dependency: Dependency,
+) -> Option<String> {
+    if store
+        .get(dependency.use_system_key())
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    let path = store.get(dependency.path_key())?;
+    let path = path.as_str()?;
+    managed_versions(store, dependency)
+        .into_iter()
+        .find(|(_, managed)| managed == path)
+        .map(|(version, _)| version)
+}
+
+/// Lists the releases available for this platform, newest first.
+pub async fn available_versions(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+) -> Result<DependencyVersions> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let installed = managed_versions(&store, dependency);
+    let active = active_managed_version(&store, dependency);
+
+    let releases = match dependency {
+        Dependency::Node => NpmHandler::releases().await?,
+        Dependency::Uv => github_releases(Dependency::Uv, UV_RELEASES_URL).await?,
+        Dependency::Bun => github_releases(Dependency::Bun, BUN_RELEASES_URL).await?,
+        Dependency::Deno => github_releases(Dependency::Deno, DENO_RELEASES_URL).await?,
+    };
+    let latest = releases
+        .iter()
+        .filter(|(_, lts)| dependency != Dependency::Node || lts.is_some())
+        .filter_map(|(version, _)| Version::parse(version).map(|parsed| (parsed, version)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, version)| version.clone());
+    let outdated = match (&active, &latest) {
+        (Some(active), Some(latest)) => Version::parse(active) < Version::parse(latest),
+        _ => false,
+    };
+
+    let mut versions: Vec<AvailableVersion> = releases
+        .into_iter()
+        .map(|(version, lts)| AvailableVersion {
+            installed: installed.contains_key(&version),
+            active: active.as_ref() == Some(&version),
+            version,
+            lts,
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(Version::parse(&v.version)));
+
+    Ok(DependencyVersions {
+        dependency,
+        active,
+        latest,
+        outdated,
+        versions,
+    })
+}
+
+/// Makes an already installed managed version the active one.
+pub fn switch_version(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+    version: &str,
+) -> Result<()> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let version = dependency.normalize_version(version)?;
+    let path = managed_versions(&store, dependency)
+        .remove(&version)
+        .filter(|path| is_valid_dir(Path::new(path), dependency))
+        .ok_or_else(|| anyhow!("{} {} is not installed", dependency.display_name(), version))?;
+    trace!("Switching {} to {}", dependency.display_name(), version);
+    activate_managed_version(&store, dependency, &version, path)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallPhase {
+    Resolving,
+    Downloading,
+    Verifying,
+    Extracting,
+    Linking,
+    Done,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InstallProgress {
+    dependency: Dependency,
+    phase: InstallPhase,
+    #[serde(rename = "bytesDownloaded")]
+    bytes_downloaded: u64,
+    /// `None` when the server does not send a `Content-Length`.
+    #[serde(rename = "bytesTotal")]
+    bytes_total: Option<u64>,
+    #[serde(rename = "filesExtracted")]
+    files_extracted: u64,
+}
+
+/// Cancellation flags of the installs currently running.
+#[derive(Default)]
+pub struct InstallState {
+    running: Mutex<HashMap<Dependency, Arc<AtomicBool>>>,
+}
+
+impl InstallState {
+    fn begin(&self, dependency: Dependency) -> Result<Arc<AtomicBool>> {
+        let mut running = self.running.lock().unwrap();
+        if running.contains_key(&dependency) {
+            return Err(anyhow!(
+                "{} is already being installed",
+                dependency.display_name()
+            ));
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        running.insert(dependency, cancelled.clone());
+        Ok(cancelled)
+    }
+
+    fn finish(&self, dependency: Dependency) {
+        self.running.lock().unwrap().remove(&dependency);
+    }
+
+    /// Asks a running install to stop. Returns whether one was running.
+    pub fn cancel(&self, dependency: Dependency) -> bool {
+        match self.running.lock().unwrap().get(&dependency) {
+            Some(cancelled) => {
+                cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Drives one dependency install: streams the archive to a temporary file
+/// while hashing it, unpacks it and reports every step as an
+/// [`InstallProgress`] event. Dropping it releases the install slot.
+struct Installer<'a, R: Runtime> {
+    app_handle: &'a tauri::AppHandle<R>,
+    cancelled: Arc<AtomicBool>,
+    progress: InstallProgress,
+}
+
+impl<'a, R: Runtime> Installer<'a, R> {
+    fn begin(app_handle: &'a tauri::AppHandle<R>, dependency: Dependency) -> Result<Self> {
+        let cancelled = app_handle.state::<InstallState>().begin(dependency)?;
+        let installer = Installer {
+            app_handle,
+            cancelled,
+            progress: InstallProgress {
+                dependency,
+                phase: InstallPhase::Resolving,
+                bytes_downloaded: 0,
+                bytes_total: None,
+                files_extracted: 0,
+            },
+        };
+        installer.emit();
+        Ok(installer)
+    }
+
+    fn emit(&self) {
+        emit_progress(self.app_handle, &self.progress);
+    }
+
+    fn phase(&mut self, phase: InstallPhase) -> Result<()> {
+        self.check_cancelled()?;
+        trace!("{:?}: {:?}", self.progress.dependency, phase);
+        self.progress.phase = phase;
+        self.emit();
+        Ok(())
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        check_cancelled(&self.cancelled, self.progress.dependency)
+    }
+
+    /// Downloads the archive at `url`, checks it against the digest listed at
+    /// `checksum_url` and unpacks it into `dest`. Returns the verified digest.
+    async fn fetch(
+        &mut self,
+        url: &str,
+        file_name: &str,
+        checksum_url: &str,
+        dest: &Path,
+    ) -> Result<String> {
+        trace!("Fetching checksum from {}", checksum_url);
+        let listing = download(checksum_url).await?;
+        let expected = find_checksum(&String::from_utf8_lossy(&listing), file_name)
+            .ok_or_else(|| anyhow!("No published checksum for {}", file_name))?;
+
+        trace!("Downloading {}", url);
+        let (archive, digest) = self.download(url, file_name, &expected).await?;
+
+        trace!("Extracting archive into {}", dest.to_string_lossy());
+        self.extract(archive.path(), dest).await?;
+        Ok(digest)
+    }
+
+    /// Streams `url` into a temporary file named after `file_name` and checks
+    /// its SHA-256 against `expected`. Returns the file and the hex digest.
+    async fn download(
+        &mut self,
+        url: &str,
+        file_name: &str,
+        expected: &str,
+    ) -> Result<(tempfile::NamedTempFile, String)> {
+        self.phase(InstallPhase::Downloading)?;
+        let mut last_emitted = 0;
+        let (file, hasher) = download_to_temp(url, file_name, |downloaded, total| {
+            self.check_cancelled()?;
+            self.progress.bytes_downloaded = downloaded;
+            self.progress.bytes_total = total;
+            if downloaded - last_emitted >= PROGRESS_BYTES_STEP {
+                last_emitted = downloaded;
+                self.emit();
+            }
+            Ok(())
+        })
+        .await?;
+        self.emit();
+
+        self.phase(InstallPhase::Verifying)?;
+        let digest = verify_sha256(file_name, hasher, expected)?;
+        Ok((file, digest))
+    }
+
+    /// Unpacks `archive` on a blocking thread into a `.partial` directory next
+    /// to `dest` and moves it into place once complete, so a failed or
+    /// cancelled install leaves nothing that looks installed.
+    async fn extract(&mut self, archive: &Path, dest: &Path) -> Result<()> {
+        self.phase(InstallPhase::Extracting)?;
+        let name = dest
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid install directory {}", dest.to_string_lossy()))?;
+        let staging = dest.with_file_name(format!("{}.partial", name.to_string_lossy()));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+
+        let app_handle = self.app_handle.clone();
+        let cancelled = self.cancelled.clone();
+        let mut progress = self.progress.clone();
+        let archive = archive.to_path_buf();
+        let target = staging.clone();
+        let extracted = tokio::task::spawn_blocking(move || {
+            archive::extract(&archive, &target, |count| {
+                check_cancelled(&cancelled, progress.dependency)?;
+                progress.files_extracted = count;
+                if count % PROGRESS_FILES_STEP == 0 {
+                    emit_progress(&app_handle, &progress);
                 }
+                Ok(())
+            })
+        })
+        .await?;
+        let files = match extracted {
+            Ok(files) => files,
+            Err(e) => {
+                if let Err(e) = fs::remove_dir_all(&staging) {
+                    trace!("Failed to remove {}: {}", staging.to_string_lossy(), e);
+                }
+                return Err(e);
             }
-            trace!("Node path does not exist: {}", node_path);
+        };
+
+        if dest.exists() {
+            fs::remove_dir_all(dest)?;
         }
-        trace!("Running check node command");
+        fs::rename(&staging, dest)?;
+        self.progress.files_extracted = files;
+        self.emit();
+        Ok(())
+    }
 
-        #[cfg(target_os = "macos")]
-        let cmd_output = cmd!(shell, "{shell_name} -ic 'which node'")
-            .quiet()
-            .read()?;
+    fn done(mut self) -> Result<()> {
+        self.phase(InstallPhase::Done)
+    }
+}
 
-        #[cfg(target_os = "windows")]
-        let cmd_output = cmd!(shell, "where.exe node").quiet().read()?;
+impl<R: Runtime> Drop for Installer<'_, R> {
+    fn drop(&mut self) {
+        self.app_handle
+            .state::<InstallState>()
+            .finish(self.progress.dependency);
+    }
+}
+
+fn emit_progress<R: Runtime>(app_handle: &tauri::AppHandle<R>, progress: &InstallProgress) {
+    if let Err(e) = app_handle.emit(INSTALL_PROGRESS_EVENT, progress) {
+        trace!("Failed to emit install progress: {}", e);
+    }
+}
+
+fn check_cancelled(cancelled: &AtomicBool, dependency: Dependency) -> Result<()> {
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(MagnetError::InstallCancelled(dependency.display_name().to_string()).into());
+    }
+    Ok(())
+}
+
+/// Downloads a small file, failing on HTTP errors instead of returning the
+/// error page.
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url).await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Finds the digest for `file` in a checksum listing made of
+/// `<sha256>  <file>` lines, as in Node's `SHASUMS256.txt`. The file may be
+/// given as a path, as in PowerShell `Get-FileHash` output. A digest without a
+/// file name (uv's `.sha256` files) is only trusted when it is all the listing
+/// holds, since otherwise nothing ties it to `file`.
+fn find_checksum(listing: &str, file: &str) -> Option<String> {
+    let lines: Vec<&str> = listing
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let single = lines.len() == 1;
+    lines.into_iter().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let digest = fields
+            .find(|field| field.len() == 64 && field.chars().all(|c| c.is_ascii_hexdigit()))?;
+        match fields.next() {
+            Some(name) if name.trim_start_matches('*').rsplit(['/', '\\']).next() == Some(file) => {
+                Some(digest.to_lowercase())
+            }
+            None if single => Some(digest.to_lowercase()),
+            _ => None,
+        }
+    })
+}
+
+/// Stable releases of a runtime published on GitHub, spelled the way
+/// [`Dependency::normalize_version`] does.
+async fn github_releases(
+    dependency: Dependency,
+    url: &str,
+) -> Result<Vec<(String, Option<String>)>> {
+    let releases: Vec<Value> = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "magnet-desktop")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(releases
+        .into_iter()
+        .filter(|release| {
+            !release["prerelease"].as_bool().unwrap_or(false)
+                && !release["draft"].as_bool().unwrap_or(false)
+        })
+        .filter_map(|release| {
+            let version = dependency
+                .normalize_version(release["tag_name"].as_str()?)
+                .ok()?;
+            Some((version, None))
+        })
+        .collect())
+}
+
+/// Installs a runtime shipped as a single release archive: checks it against
+/// the digest listed at `checksum_url`, unpacks it into `dest` and makes the
+/// executables found inside the active install. Returns the verified digest.
+async fn install_release(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+    version: &str,
+    url: &str,
+    checksum_url: &str,
+    dest: &Path,
+) -> Result<String> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let archive_name = url.rsplit('/').next().unwrap_or(url);
+    let mut installer = Installer::begin(app_handle, dependency)?;
+    let digest = installer
+        .fetch(url, archive_name, checksum_url, dest)
+        .await?;
 
-        trace!("Node command output: {}", cmd_output);
-        store.set("node_path", cmd_output);
-        store.set("use_system_node", true);
+    installer.phase(InstallPhase::Linking)?;
+    let bin_dir = find_tool_dir(dest, dependency).ok_or_else(|| {
+        anyhow!(
+            "{} does not contain {}",
+            archive_name,
+            dependency.display_name()
+        )
+    })?;
+    activate_managed_version(
+        &store,
+        dependency,
+        version,
+        bin_dir.to_string_lossy().to_string(),
+    )?;
+    installer.done()?;
+    trace!("All done");
+    Ok(digest)
+}
+
+/// Streams `url` into a temporary file named after `file_name`, hashing it on
+/// the way. `on_chunk` is called with the bytes downloaded so far and the
+/// total size, if known, and aborts the download by returning an error.
+pub(crate) async fn download_to_temp<F>(
+    url: &str,
+    file_name: &str,
+    mut on_chunk: F,
+) -> Result<(tempfile::NamedTempFile, Sha256)>
+where
+    F: FnMut(u64, Option<u64>) -> Result<()>,
+{
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let total = response.content_length();
+    let mut file = tempfile::Builder::new()
+        .prefix("magnet-")
+        .suffix(&format!("-{}", file_name))
+        .tempfile()?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        on_chunk(downloaded, total)?;
+    }
+    file.flush()?;
+    Ok((file, hasher))
+}
 
-        Ok(true)
+/// Checks the hashed bytes against the expected SHA-256 and returns the hex
+/// digest.
+pub(crate) fn verify_sha256(file: &str, hasher: Sha256, expected: &str) -> Result<String> {
+    let actual = hex::encode(hasher.finalize());
+    if actual != expected {
+        return Err(MagnetError::ChecksumMismatch {
+            file: file.to_string(),
+            expected: expected.to_string(),
+            actual,
+        }
+        .into());
     }
+    trace!("Verified {} ({})", file, actual);
+    Ok(actual)
+}
 
-    pub async fn install(app_handle: &tauri::AppHandle) -> Result<()> {
+impl NpmHandler {
+    /// Resolves the install servers are launched with; see [`resolve`].
+    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
+        Ok(resolve(app_handle, Dependency::Node).await?.is_some())
+    }
+
+    /// Key of this platform's build in the `files` list of Node's `index.json`.
+    fn index_file() -> &'static str {
+        #[cfg(target_os = "macos")]
+        {
+            #[cfg(target_arch = "aarch64")]
+            {
+                "osx-arm64-tar"
+            }
+            #[cfg(target_arch = "x86_64")]
+            {
+                "osx-x64-tar"
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            #[cfg(target_arch = "aarch64")]
+            {
+                "linux-arm64"
+            }
+            #[cfg(target_arch = "x86_64")]
+            {
+                "linux-x64"
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            #[cfg(target_arch = "x86_64")]
+            {
+                "win-x64-zip"
+            }
+            #[cfg(target_arch = "x86")]
+            {
+                "win-x86-zip"
+            }
+            #[cfg(target_arch = "aarch64")]
+            {
+                "win-arm64-zip"
+            }
+        }
+    }
+
+    /// Releases published for this platform with their LTS codename.
+    async fn releases() -> Result<Vec<(String, Option<String>)>> {
+        let index: Vec<Value> = reqwest::get(NODE_INDEX_URL)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let platform = Self::index_file();
+        Ok(index
+            .into_iter()
+            .filter(|release| {
+                release["files"]
+                    .as_array()
+                    .is_some_and(|files| files.iter().any(|f| f.as_str() == Some(platform)))
+            })
+            .filter_map(|release| {
+                let version = release["version"].as_str()?.to_string();
+                let lts = release["lts"].as_str().map(String::from);
+                Some((version, lts))
+            })
+            .collect())
+    }
+
+    /// Installs a Node.js release next to any already installed ones and makes
+    /// it the active one. The archive is checked against the published
+    /// `SHASUMS256.txt`. Returns the verified SHA-256 digest.
+    pub async fn install(app_handle: &tauri::AppHandle, version: Option<&str>) -> Result<String> {
         trace!("Installing Node.js");
         let store = app_handle.store(APP_STATE_FILENAME)?;
-        let home_dir_str = get_home()?.to_string_lossy().to_string();
-        let node_version = "v22.11.0";
+        let node_version =
+            Dependency::Node.normalize_version(version.unwrap_or(DEFAULT_NODE_VERSION))?;
+        let node_version = node_version.as_str();
 
         let node_arch = {
             #[cfg(target_os = "macos")]
@@ -73,6 +754,17 @@ impl NpmHandler {
                     "darwin-x64.tar.gz"
                 }
             }
+            #[cfg(target_os = "linux")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "linux-arm64.tar.gz"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "linux-x64.tar.gz"
+                }
+            }
             #[cfg(target_os = "windows")]
             {
                 #[cfg(target_arch = "x86_64")]
@@ -90,87 +782,53 @@ impl NpmHandler {
             }
         };
 
-        let node_download_url = format!(
-            "https://nodejs.org/dist/{}/node-{}-{}",
-            node_version, node_version, node_arch
-        );
+        let node_archive = format!("node-{}-{}", node_version, node_arch);
+        let node_download_url =
+            format!("https://nodejs.org/dist/{}/{}", node_version, node_archive);
         trace!("Downloading node from {}", node_download_url);
 
-        #[cfg(target_os = "macos")]
-        let node_dir = format!("{}/.node", home_dir_str);
-        #[cfg(target_os = "windows")]
-        let node_dir = format!("{}\\AppData\\Local\\node", home_dir_str);
-
-        trace!("Creating node directory at {}", node_dir);
-        fs::create_dir_all(&node_dir)?;
+        let node_dir = Dependency::Node.managed_root()?.join(node_version);
 
-        trace!("Downloading node.js");
-        let response = reqwest::get(node_download_url).await?;
-        let bytes = response.bytes().await?;
+        let mut installer = Installer::begin(app_handle, Dependency::Node)?;
+        let digest = installer
+            .fetch(
+                &node_download_url,
+                &node_archive,
+                &format!("https://nodejs.org/dist/{}/SHASUMS256.txt", node_version),
+                &node_dir,
+            )
+            .await?;
 
-        trace!("Extracting archive");
-        #[cfg(target_os = "macos")]
-        {
-            let gz = GzDecoder::new(Cursor::new(bytes));
-            let mut archive = Archive::new(gz);
-            archive.unpack(&node_dir)?;
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            let cursor = Cursor::new(bytes);
-            let mut archive = ZipArchive::new(cursor)?;
-            archive.extract(&node_dir)?;
-        }
-
-        store.set("node_path", node_dir);
-        store.set("use_system_node", false);
+        installer.phase(InstallPhase::Linking)?;
+        // The archive unpacks into `node-<version>-<platform>`.
+        let node_bin_dir = find_tool_dir(&node_dir, Dependency::Node)
+            .ok_or_else(|| anyhow!("{} does not contain node", node_archive))?;
+        activate_managed_version(
+            &store,
+            Dependency::Node,
+            node_version,
+            node_bin_dir.to_string_lossy().to_string(),
+        )?;
+        installer.done()?;
         trace!("All done");
-        Ok(())
+        Ok(digest)
     }
 }
 
 impl UVHandler {
+    /// Resolves the install servers are launched with; see [`resolve`].
     pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
-        let store = app_handle.store(APP_STATE_FILENAME).unwrap();
-        let shell = Shell::new()?;
-        let shell_name = detect_shell()?;
-
-        let uv_path = store
-            .get("uv_path")
-            .and_then(|s| s.as_str().map(String::from))
-            .unwrap_or("".to_owned());
-
-        if !uv_path.is_empty() {
-            if let Ok(metadata) = fs::metadata(&uv_path) {
-                if metadata.is_dir() || metadata.is_symlink() || metadata.is_file() {
-                    trace!("UV path exists: {}", uv_path);
-                    return Ok(true);
-                }
-            }
-            trace!("UV path does not exist: {}", uv_path);
-        }
-
-        trace!("Running check node command");
-
-        #[cfg(target_os = "macos")]
-        let cmd_output = cmd!(shell, "{shell_name} -ic 'which uv'").read()?;
-
-        #[cfg(target_os = "windows")]
-        let cmd_output = cmd!(shell, "where.exe uv").quiet().read()?;
-        trace!("uv command output: {}", cmd_output);
-
-        store.set("uv_path", cmd_output);
-        store.set("use_system_uv", true);
-
-        Ok(true)
+        Ok(resolve(app_handle, Dependency::Uv).await?.is_some())
     }
 
-    pub async fn install(app_handle: &tauri::AppHandle) -> Result<()> {
+    /// Installs a uv release next to any already installed ones and makes it
+    /// the active one. The archive is checked against the `.sha256` file
+    /// published next to it. Returns the verified SHA-256 digest.
+    pub async fn install(app_handle: &tauri::AppHandle, version: Option<&str>) -> Result<String> {
         trace!("Installing UV");
         let store = app_handle.store(APP_STATE_FILENAME)?;
-        let home_dir_str = get_home()?.to_string_lossy().to_string();
-        let uv_version = "0.5.5";
+        let uv_version = Dependency::Uv.normalize_version(version.unwrap_or(DEFAULT_UV_VERSION))?;
+        let uv_version = uv_version.as_str();
         let uv_arch = {
             #[cfg(target_os = "macos")]
             {
@@ -183,6 +841,17 @@ impl UVHandler {
                     "x86_64-apple-darwin.tar.gz"
                 }
             }
+            #[cfg(target_os = "linux")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "aarch64-unknown-linux-gnu.tar.gz"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "x86_64-unknown-linux-gnu.tar.gz"
+                }
+            }
             #[cfg(target_os = "windows")]
             {
                 #[cfg(target_arch = "x86_64")]
@@ -196,64 +865,310 @@ impl UVHandler {
             }
         };
 
+        let uv_archive = format!("uv-{}", uv_arch);
         let uv_download_url = format!(
-            "https://github.com/astral-sh/uv/releases/download/{}/uv-{}",
-            uv_version, uv_arch
+            "https://github.com/astral-sh/uv/releases/download/{}/{}",
+            uv_version, uv_archive
         );
 
         trace!("Downloading uv from {}", uv_download_url);
 
-        #[cfg(target_os = "macos")]
-        let uv_dir = format!("{}/.uv/bin", home_dir_str);
-        #[cfg(target_os = "windows")]
-        let uv_dir = format!("{}\\AppData\\Local\\uv\\bin", home_dir_str);
+        let uv_dir = Dependency::Uv.managed_root()?.join(uv_version);
 
-        trace!("Creating uv directory at {}", uv_dir);
-        fs::create_dir_all(&uv_dir)?;
+        let mut installer = Installer::begin(app_handle, Dependency::Uv)?;
+        let digest = installer
+            .fetch(
+                &uv_download_url,
+                &uv_archive,
+                &format!("{}.sha256", uv_download_url),
+                &uv_dir,
+            )
+            .await?;
 
-        trace!("Downloading uv");
-        let response = reqwest::get(uv_download_url).await?;
-        let bytes = response.bytes().await?;
+        installer.phase(InstallPhase::Linking)?;
+        let uv_bin_dir = find_tool_dir(&uv_dir, Dependency::Uv)
+            .ok_or_else(|| anyhow!("{} does not contain uv", uv_archive))?;
+        activate_managed_version(
+            &store,
+            Dependency::Uv,
+            uv_version,
+            uv_bin_dir.to_string_lossy().to_string(),
+        )?;
+        installer.done()?;
+        trace!("All done");
+        Ok(digest)
+    }
+}
 
-        trace!("Extracting archive");
-        #[cfg(target_os = "macos")]
-        {
-            let gz = GzDecoder::new(Cursor::new(bytes));
-            let mut archive = Archive::new(gz);
-            archive.unpack(&uv_dir)?;
-        }
-        #[cfg(target_os = "windows")]
-        {
-            let cursor = Cursor::new(bytes);
-            let mut archive = ZipArchive::new(cursor)?;
-            archive.extract(&uv_dir)?;
-        }
+impl BunHandler {
+    /// Resolves the install servers are launched with; see [`resolve`].
+    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
+        Ok(resolve(app_handle, Dependency::Bun).await?.is_some())
+    }
 
-        store.set(
-            "uv_path",
-            format!("{}/uv-{}", uv_dir, uv_arch.split(".").next().unwrap()),
+    /// Installs a Bun release next to any already installed ones and makes it
+    /// the active one. The archive is checked against the release's
+    /// `SHASUMS256.txt`. Returns the verified SHA-256 digest.
+    pub async fn install(app_handle: &tauri::AppHandle, version: Option<&str>) -> Result<String> {
+        trace!("Installing Bun");
+        let bun_version =
+            Dependency::Bun.normalize_version(version.unwrap_or(DEFAULT_BUN_VERSION))?;
+        let bun_target = {
+            #[cfg(target_os = "macos")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "darwin-aarch64"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "darwin-x64"
+                }
+                #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+                {
+                    return Err(anyhow!("Bun is not available for this platform"));
+                }
+            }
+            #[cfg(target_os = "linux")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "linux-aarch64"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "linux-x64"
+                }
+                #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+                {
+                    return Err(anyhow!("Bun is not available for this platform"));
+                }
+            }
+            #[cfg(target_os = "windows")]
+            {
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "windows-x64"
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                {
+                    return Err(anyhow!("Bun is not available for this platform"));
+                }
+            }
+        };
+
+        let release_url = format!(
+            "https://github.com/oven-sh/bun/releases/download/bun-v{}",
+            bun_version
         );
-        store.set("use_system_uv", false);
-        trace!("All done");
-        Ok(())
+        install_release(
+            app_handle,
+            Dependency::Bun,
+            &bun_version,
+            &format!("{}/bun-{}.zip", release_url, bun_target),
+            &format!("{}/SHASUMS256.txt", release_url),
+            &Dependency::Bun.managed_root()?.join(&bun_version),
+        )
+        .await
     }
 }
 
-impl ResourceHandler {
-    async fn download(app_handle: &tauri::AppHandle) -> Result<()> {
-        let store = app_handle.store(APP_STATE_FILENAME)?;
-        trace!("Start download servers.json");
-        let servers_json = reqwest::get(SERVERS_URL).await?.text().await?;
-        trace!("servers.json: {}", servers_json);
-        store.set("servers", servers_json);
-        trace!("servers.json set in store");
-        Ok(())
+impl DenoHandler {
+    /// Resolves the install servers are launched with; see [`resolve`].
+    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
+        Ok(resolve(app_handle, Dependency::Deno).await?.is_some())
     }
 
-    pub async fn detect(app_handle: &tauri::AppHandle) -> Result<bool> {
-        trace!("Start download servers.json when resource not found");
-        Self::download(app_handle).await?;
-        trace!("End download servers.json when resource not found");
-        Ok(true)
+    /// Installs a Deno release next to any already installed ones and makes it
+    /// the active one. The archive is checked against the `.sha256sum` file
+    /// published next to it. Returns the verified SHA-256 digest.
+    pub async fn install(app_handle: &tauri::AppHandle, version: Option<&str>) -> Result<String> {
+        trace!("Installing Deno");
+        let deno_version =
+            Dependency::Deno.normalize_version(version.unwrap_or(DEFAULT_DENO_VERSION))?;
+        let deno_target = {
+            #[cfg(target_os = "macos")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "aarch64-apple-darwin"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "x86_64-apple-darwin"
+                }
+                #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+                {
+                    return Err(anyhow!("Deno is not available for this platform"));
+                }
+            }
+            #[cfg(target_os = "linux")]
+            {
+                #[cfg(target_arch = "aarch64")]
+                {
+                    "aarch64-unknown-linux-gnu"
+                }
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "x86_64-unknown-linux-gnu"
+                }
+                #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+                {
+                    return Err(anyhow!("Deno is not available for this platform"));
+                }
+            }
+            #[cfg(target_os = "windows")]
+            {
+                #[cfg(target_arch = "x86_64")]
+                {
+                    "x86_64-pc-windows-msvc"
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                {
+                    return Err(anyhow!("Deno is not available for this platform"));
+                }
+            }
+        };
+
+        let deno_download_url = format!(
+            "https://github.com/denoland/deno/releases/download/{}/deno-{}.zip",
+            deno_version, deno_target
+        );
+        install_release(
+            app_handle,
+            Dependency::Deno,
+            &deno_version,
+            &deno_download_url,
+            &format!("{}.sha256sum", deno_download_url),
+            &Dependency::Deno.managed_root()?.join(&deno_version),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    const BODY: &[u8] = b"not really an archive";
+
+    /// Serves `body` to a single request on a local port and returns its URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn finds_checksum_for_file() {
+        let listing = format!(
+            "{}  node-v22.0.0-linux-x64.tar.gz\n{}  node-v22.0.0-darwin-arm64.tar.gz\n",
+            "a".repeat(64),
+            "B".repeat(64)
+        );
+        assert_eq!(
+            find_checksum(&listing, "node-v22.0.0-darwin-arm64.tar.gz"),
+            Some("b".repeat(64))
+        );
+        assert_eq!(find_checksum(&listing, "node-v22.0.0-win-x64.zip"), None);
+    }
+
+    #[test]
+    fn finds_checksum_in_other_formats() {
+        let digest = "c".repeat(64);
+        // Binary mode marker of `sha256sum -b`.
+        let listing = format!("{} *uv-x86_64-unknown-linux-gnu.tar.gz\n", digest);
+        assert_eq!(
+            find_checksum(&listing, "uv-x86_64-unknown-linux-gnu.tar.gz"),
+            Some(digest.clone())
+        );
+        // A lone bare digest belongs to the file it was published next to.
+        assert_eq!(
+            find_checksum(&format!("\n{}\n\n", digest), "deno.zip"),
+            Some(digest.clone())
+        );
+        // PowerShell `Get-FileHash` output.
+        let listing = format!(
+            "\nAlgorithm Hash Path\n--------- ---- ----\nSHA256 {} D:\\a\\release\\deno.zip\n",
+            digest.to_uppercase()
+        );
+        assert_eq!(find_checksum(&listing, "deno.zip"), Some(digest.clone()));
+        assert_eq!(find_checksum(&listing, "bun.zip"), None);
+        assert_eq!(find_checksum("not a checksum\n", "bun.zip"), None);
+    }
+
+    #[test]
+    fn ignores_bare_digests_among_other_lines() {
+        let listing = format!("{}\n{}  other.zip\n", "d".repeat(64), "e".repeat(64));
+        assert_eq!(find_checksum(&listing, "deno.zip"), None);
+        let listing = format!("Algorithm Hash Path\nSHA256 {}\n", "d".repeat(64));
+        assert_eq!(find_checksum(&listing, "deno.zip"), None);
+    }
+
+    #[tokio::test]
+    async fn verifies_downloaded_archive() {
+        let expected = hex::encode(Sha256::digest(BODY));
+        let (file, hasher) = download_to_temp(&serve_once(BODY), "archive.tar.gz", |_, _| Ok(()))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(file.path()).unwrap(), BODY);
+        assert_eq!(
+            verify_sha256("archive.tar.gz", hasher, &expected).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_mismatch() {
+        let app = tauri::test::mock_app();
+        app.manage(InstallState::default());
+        let managed_dir = tempfile::tempdir().unwrap();
+        let dest = managed_dir.path().join("v1.0.0");
+        let expected = "0".repeat(64);
+        let listing = format!("{}  archive.tar.gz\n", expected)
+            .into_bytes()
+            .leak();
+
+        let mut installer = Installer::begin(app.handle(), Dependency::Bun).unwrap();
+        let error = installer
+            .fetch(
+                &serve_once(BODY),
+                "archive.tar.gz",
+                &serve_once(listing),
+                &dest,
+            )
+            .await
+            .unwrap_err();
+        match error.downcast_ref::<MagnetError>() {
+            Some(MagnetError::ChecksumMismatch {
+                file,
+                expected: wanted,
+                actual,
+            }) => {
+                assert_eq!(file, "archive.tar.gz");
+                assert_eq!(wanted, &expected);
+                assert_eq!(actual, &hex::encode(Sha256::digest(BODY)));
+            }
+            other => panic!("expected a checksum mismatch, got {:?}", other),
+        }
+        assert!(!dest.exists());
+        assert!(!managed_dir.path().join("v1.0.0.partial").exists());
     }
 }
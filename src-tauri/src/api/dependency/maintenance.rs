@@ -0,0 +1,206 @@
+use super::core::{
+    active_managed_version, forget_managed_versions, managed_versions, switch_version, BunHandler,
+    DenoHandler, Dependency, NpmHandler, UVHandler,
+};
+use super::resolver::{is_valid_dir, resolve, tool_dir};
+use super::runtime::{runtime_status, RuntimeStatus};
+use crate::api::clients::core::McpClient;
+use crate::api::servers::core::load_detected_client_configs;
+use crate::error::MagnetError;
+use crate::APP_STATE_FILENAME;
+use anyhow::{anyhow, Result};
+use log::{trace, warn};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri_plugin_store::StoreExt;
+
+/// A Magnet-created server entry launched through a managed runtime.
+#[derive(Debug, Serialize, Clone)]
+pub struct DependentServer {
+    id: String,
+    client: McpClient,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UninstallReport {
+    /// Versions whose files were removed.
+    removed: Vec<String>,
+    /// Servers that will no longer start until they are reinstalled.
+    dependents: Vec<DependentServer>,
+}
+
+/// The directory directly under `root` that holds `dir`, i.e. what has to be
+/// deleted to remove one managed install.
+fn install_root(root: &Path, dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.parent() == Some(root))
+        .map(Path::to_path_buf)
+}
+
+fn remove_install(root: &Path, dir: &Path) -> Result<()> {
+    match install_root(root, dir) {
+        Some(install) if install.exists() => {
+            trace!("Removing {}", install.to_string_lossy());
+            fs::remove_dir_all(&install)?;
+        }
+        Some(_) => {}
+        None => warn!(
+            "Not removing {}: outside of {}",
+            dir.to_string_lossy(),
+            root.to_string_lossy()
+        ),
+    }
+    Ok(())
+}
+
+/// Magnet-created servers whose launch command refers to one of `dirs`.
+async fn dependent_servers(app_handle: &tauri::AppHandle, dirs: &[PathBuf]) -> Vec<DependentServer> {
+    let dirs: Vec<String> = dirs
+        .iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+    let mut dependents = vec![];
+    for (client, configs) in load_detected_client_configs(app_handle).await {
+        for (id, config) in configs {
+            if config.command_creator != "Magnet" {
+                continue;
+            }
+            let mentions = |text: &String| dirs.iter().any(|dir| text.contains(dir.as_str()));
+            if mentions(&config.command)
+                || config.args.iter().any(mentions)
+                || config.env.values().any(mentions)
+            {
+                dependents.push(DependentServer { id, client });
+            }
+        }
+    }
+    dependents.sort_by(|a, b| a.id.cmp(&b.id));
+    dependents
+}
+
+/// Removes one managed version, or all of them when `version` is `None`.
+/// Fails with `DependencyInUse` while Magnet-created servers still launch
+/// through them, unless `force` is set. Afterwards another install is
+/// resolved, if there is one.
+pub async fn uninstall(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+    version: Option<&str>,
+    force: bool,
+) -> Result<UninstallReport> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let root = dependency.managed_root()?;
+    let mut installed: Vec<(String, String)> =
+        managed_versions(&store, dependency).into_iter().collect();
+    match version {
+        Some(version) => {
+            let version = dependency.normalize_version(version)?;
+            installed.retain(|(installed, _)| *installed == version);
+            if installed.is_empty() {
+                return Err(anyhow!(
+                    "{} {} is not installed",
+                    dependency.display_name(),
+                    version
+                ));
+            }
+        }
+        None => {
+            // Installs made before versions were recorded only live in the path key.
+            let use_system = store
+                .get(dependency.use_system_key())
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let stored = store
+                .get(dependency.path_key())
+                .and_then(|v| v.as_str().map(String::from));
+            if let Some(stored) = stored.filter(|_| !use_system) {
+                if !installed.iter().any(|(_, path)| *path == stored) {
+                    if let Some(install) = install_root(&root, &tool_dir(&stored)) {
+                        let name = install.file_name().unwrap_or_default();
+                        installed.push((name.to_string_lossy().to_string(), stored));
+                    }
+                }
+            }
+        }
+    }
+    if installed.is_empty() {
+        return Err(MagnetError::DependencyMissing(dependency.display_name().to_string()).into());
+    }
+
+    let dirs: Vec<PathBuf> = installed
+        .iter()
+        .map(|(_, path)| PathBuf::from(path))
+        .collect();
+    let dependents = dependent_servers(app_handle, &dirs).await;
+    if !dependents.is_empty() && !force {
+        return Err(MagnetError::DependencyInUse {
+            dependency: dependency.display_name().to_string(),
+            servers: dependents.iter().map(|server| server.id.clone()).collect(),
+        }
+        .into());
+    }
+
+    for dir in &dirs {
+        remove_install(&root, dir)?;
+    }
+    let removed: Vec<String> = installed.into_iter().map(|(version, _)| version).collect();
+    forget_managed_versions(&store, dependency, &removed)?;
+    resolve(app_handle, dependency).await?;
+    Ok(UninstallReport {
+        removed,
+        dependents,
+    })
+}
+
+async fn reinstall(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+    version: &str,
+) -> Result<()> {
+    match dependency {
+        Dependency::Node => NpmHandler::install(app_handle, Some(version)).await?,
+        Dependency::Uv => UVHandler::install(app_handle, Some(version)).await?,
+        Dependency::Bun => BunHandler::install(app_handle, Some(version)).await?,
+        Dependency::Deno => DenoHandler::install(app_handle, Some(version)).await?,
+    };
+    Ok(())
+}
+
+/// Reinstalls managed versions that are missing executables or fail to run,
+/// keeping the active version active. Installs the default version when there
+/// is no install at all. System installs are only reported, never modified.
+pub async fn repair(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+) -> Result<RuntimeStatus> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let root = dependency.managed_root()?;
+    let status = runtime_status(app_handle, dependency).await?;
+    let active = active_managed_version(&store, dependency);
+
+    let mut broken: Vec<(String, String)> = managed_versions(&store, dependency)
+        .into_iter()
+        .filter(|(version, path)| {
+            !is_valid_dir(Path::new(path), dependency)
+                || (active.as_ref() == Some(version) && !status.is_healthy())
+        })
+        .collect();
+    if broken.is_empty() && resolve(app_handle, dependency).await?.is_none() {
+        broken.push((dependency.default_version().to_string(), String::new()));
+    }
+
+    for (version, path) in &broken {
+        trace!("Repairing {} {}", dependency.display_name(), version);
+        if !path.is_empty() {
+            remove_install(&root, Path::new(path))?;
+        }
+        reinstall(app_handle, dependency, version).await?;
+    }
+    if let Some(active) = active {
+        if broken.iter().any(|(version, _)| *version != active) {
+            switch_version(app_handle, dependency, &active)?;
+        }
+    }
+    runtime_status(app_handle, dependency).await
+}
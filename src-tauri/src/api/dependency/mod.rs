@@ -1,2 +1,7 @@
+pub mod container;
 pub mod core;
+pub mod maintenance;
+pub mod registry;
+pub mod resolver;
+pub mod runtime;
 pub mod view;
@@ -0,0 +1,729 @@
+use crate::APP_STATE_FILENAME;
+use anyhow::{anyhow, Context, Result};
+use log::{trace, warn};
+use minisign_verify::{PublicKey, Signature};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Wry;
+use tauri_plugin_store::{Store, StoreExt};
+
+pub struct ResourceHandler;
+
+const SERVERS_URL: &str = "https://file.magnetlabs.xyz/server-configuration/servers-v0.1.json";
+/// Id of the built-in Magnet registry.
+pub const DEFAULT_SOURCE_ID: &str = "magnet";
+/// minisign public key the registry must be signed with. Defaults to the
+/// release signing key (the updater `pubkey` in `tauri.conf.json`); builds can
+/// point it elsewhere with `MAGNET_REGISTRY_PUBKEY`.
+const REGISTRY_PUBKEY: &str = match option_env!("MAGNET_REGISTRY_PUBKEY") {
+    Some(pubkey) => pubkey,
+    None => "RWSC+jzfNpHFrqKktYrVqd7r1FKDAbDAq/KPc50xKiASXJhGqfHvKXKg",
+};
+/// How long a downloaded registry is used before it is revalidated.
+pub const DEFAULT_REGISTRY_TTL_SECS: u64 = 60 * 60;
+
+/// A catalog of servers. `location` is an `https://` URL, or a `file://`
+/// URL (or plain path) of either a JSON file or a directory of per-server
+/// JSON files.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegistrySource {
+    id: String,
+    location: String,
+    /// minisign public key. Required for `https://` sources, which must serve
+    /// a valid `<location>.minisig` signature.
+    #[serde(default)]
+    pubkey: Option<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    /// Lets the source replace servers of the built-in registry. Each
+    /// replacement still has to be acknowledged before it is used.
+    #[serde(default, rename = "overrideBuiltin")]
+    override_builtin: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+enum SourceLocation {
+    Http(String),
+    File(PathBuf),
+    Directory(PathBuf),
+}
+
+impl RegistrySource {
+    fn builtin() -> Self {
+        RegistrySource {
+            id: DEFAULT_SOURCE_ID.to_string(),
+            location: SERVERS_URL.to_string(),
+            pubkey: Some(REGISTRY_PUBKEY.to_string()),
+            enabled: true,
+            override_builtin: false,
+        }
+    }
+
+    fn resolve(&self) -> Result<SourceLocation> {
+        let scheme = self
+            .location
+            .split_once("://")
+            .map(|(scheme, _)| scheme.to_ascii_lowercase());
+        match scheme.as_deref() {
+            Some("http") => {
+                return Err(anyhow!(
+                    "Registry source {} must use https:// instead of http://",
+                    self.id
+                ))
+            }
+            Some("https") if self.pubkey.is_none() => {
+                return Err(anyhow!(
+                    "Registry source {} is remote and needs a pubkey to verify it",
+                    self.id
+                ))
+            }
+            Some("https") => return Ok(SourceLocation::Http(self.location.clone())),
+            _ => {}
+        }
+        let path = if self.location.starts_with("file://") {
+            Url::parse(&self.location)?
+                .to_file_path()
+                .map_err(|_| anyhow!("Invalid file URL: {}", self.location))?
+        } else {
+            PathBuf::from(&self.location)
+        };
+        if path.is_dir() {
+            Ok(SourceLocation::Directory(path))
+        } else {
+            Ok(SourceLocation::File(path))
+        }
+    }
+}
+
+/// Bookkeeping for a downloaded registry.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct RegistryMeta {
+    #[serde(rename = "fetchedAt")]
+    fetched_at: Option<u64>,
+    etag: Option<String>,
+    #[serde(rename = "lastModified")]
+    last_modified: Option<String>,
+    /// Set when revalidation failed and the cached copy is served instead.
+    #[serde(rename = "staleSince")]
+    stale_since: Option<u64>,
+}
+
+/// Last good copy of an HTTP source.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SourceCache {
+    body: String,
+    signature: Option<String>,
+    meta: RegistryMeta,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SourceStatus {
+    #[serde(flatten)]
+    source: RegistrySource,
+    #[serde(flatten)]
+    meta: RegistryMeta,
+    stale: bool,
+    servers: usize,
+    error: Option<String>,
+}
+
+/// A server id offered by more than one source; the one from `source` wins.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegistryCollision {
+    id: String,
+    source: String,
+    shadowed: String,
+    /// Set when `shadowed` may override the built-in registry but the user
+    /// has not acknowledged the replacement yet; until then the signed
+    /// server is used.
+    #[serde(default)]
+    blocking: bool,
+}
+
+/// A registry source's catalog, as merged by `merge_sources`.
+struct Catalog {
+    source_id: String,
+    override_builtin: bool,
+    servers: Vec<Value>,
+}
+
+/// Key of an acknowledged replacement of a built-in server.
+fn override_key(source_id: &str, server_id: &str) -> String {
+    format!("{}/{}", source_id, server_id)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegistryStatus {
+    #[serde(rename = "ttlSecs")]
+    ttl_secs: u64,
+    sources: Vec<SourceStatus>,
+    collisions: Vec<RegistryCollision>,
+}
+
+impl RegistryStatus {
+    pub fn blocking_collisions(self) -> Vec<RegistryCollision> {
+        self.collisions
+            .into_iter()
+            .filter(|collision| collision.blocking)
+            .collect()
+    }
+}
+
+/// Checks `body` against a detached minisign signature made with `pubkey`.
+fn verify_registry(body: &str, signature: &str, pubkey: &str) -> Result<()> {
+    let public_key = PublicKey::from_base64(pubkey).context("Invalid registry public key")?;
+    let signature = Signature::decode(signature).context("Invalid registry signature")?;
+    public_key
+        .verify(body.as_bytes(), &signature, false)
+        .context("Registry signature does not match")?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parses a catalog document: either an array of servers or a single server.
+fn parse_servers(document: &str) -> Result<Vec<Value>> {
+    match serde_json::from_str::<Value>(document)? {
+        Value::Array(servers) => Ok(servers),
+        server @ Value::Object(_) => Ok(vec![server]),
+        _ => Err(anyhow!("Expected a server or a list of servers")),
+    }
+}
+
+/// Merges catalogs listed from highest to lowest precedence, tagging every
+/// server with the id of the source it came from. Servers of the built-in
+/// registry win over every other source, except for replacements from a
+/// source with `override_builtin` that are listed in `acknowledged`.
+fn merge_sources(
+    catalogs: Vec<Catalog>,
+    acknowledged: &HashSet<String>,
+) -> (Vec<Value>, Vec<RegistryCollision>) {
+    let builtin_ids: HashSet<String> = catalogs
+        .iter()
+        .filter(|catalog| catalog.source_id == DEFAULT_SOURCE_ID)
+        .flat_map(|catalog| &catalog.servers)
+        .filter_map(|server| server["id"].as_str().map(String::from))
+        .collect();
+    let mut merged = vec![];
+    let mut owners: HashMap<String, String> = HashMap::new();
+    let mut collisions = vec![];
+    for catalog in catalogs {
+        let source_id = catalog.source_id;
+        let mut seen_in_source = HashSet::new();
+        for mut server in catalog.servers {
+            let Some(id) = server["id"].as_str().map(String::from) else {
+                warn!("Skipping server without an id from {}", source_id);
+                continue;
+            };
+            if !seen_in_source.insert(id.clone()) {
+                warn!("Duplicate server {} within {}", id, source_id);
+                continue;
+            }
+            if source_id != DEFAULT_SOURCE_ID
+                && builtin_ids.contains(&id)
+                && !(catalog.override_builtin
+                    && acknowledged.contains(&override_key(&source_id, &id)))
+            {
+                collisions.push(RegistryCollision {
+                    id,
+                    source: DEFAULT_SOURCE_ID.to_string(),
+                    shadowed: source_id.clone(),
+                    blocking: catalog.override_builtin,
+                });
+                continue;
+            }
+            if let Some(owner) = owners.get(&id) {
+                collisions.push(RegistryCollision {
+                    id,
+                    source: owner.clone(),
+                    shadowed: source_id.clone(),
+                    blocking: false,
+                });
+                continue;
+            }
+            server["source"] = Value::String(source_id.clone());
+            owners.insert(id, source_id.clone());
+            merged.push(server);
+        }
+    }
+    (merged, collisions)
+}
+
+impl ResourceHandler {
+    fn ttl(store: &Store<Wry>) -> u64 {
+        store
+            .get("registry_ttl_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_REGISTRY_TTL_SECS)
+    }
+
+    fn user_sources(store: &Store<Wry>) -> Vec<RegistrySource> {
+        store
+            .get("registry_sources")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    /// All sources from highest to lowest precedence: user sources in the
+    /// order they are listed, then the built-in Magnet registry, whose
+    /// servers `merge_sources` still keeps unless an override is acknowledged.
+    fn sources(store: &Store<Wry>) -> Vec<RegistrySource> {
+        let mut sources = Self::user_sources(store);
+        sources.push(RegistrySource::builtin());
+        sources
+    }
+
+    fn acknowledged_overrides(store: &Store<Wry>) -> HashSet<String> {
+        store
+            .get("registry_override_acks")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn caches(store: &Store<Wry>) -> HashMap<String, SourceCache> {
+        store
+            .get("registry_cache")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether a cached copy is intact, i.e. still carries a valid signature
+    /// when the source requires one.
+    fn cache_is_valid(source: &RegistrySource, cache: &SourceCache) -> bool {
+        match (&source.pubkey, &cache.signature) {
+            (Some(pubkey), Some(signature)) => {
+                verify_registry(&cache.body, signature, pubkey).is_ok()
+            }
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Fetches an HTTP source, sending the cached validators so an unchanged
+    /// registry costs a `304 Not Modified` instead of a full download. A
+    /// signed source is only accepted once its signature has been verified.
+    async fn download(
+        source: &RegistrySource,
+        url: &str,
+        cached: Option<&SourceCache>,
+    ) -> Result<SourceCache> {
+        trace!("Start download {}", url);
+        let client = reqwest::Client::new();
+        let mut request = client.get(url);
+        if let Some(cache) = cached {
+            if let Some(etag) = &cache.meta.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cache.meta.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cache) = cached {
+                trace!("{} not modified", url);
+                let mut cache = cache.clone();
+                cache.meta.fetched_at = Some(unix_now());
+                cache.meta.stale_since = None;
+                return Ok(cache);
+            }
+        }
+
+        let response = response.error_for_status()?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let body = response.text().await?;
+        trace!("{}: {}", url, body);
+
+        let signature = match &source.pubkey {
+            Some(pubkey) => {
+                let signature = client
+                    .get(format!("{}.minisig", url))
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .map_err(|e| anyhow!("{} is not signed: {}", url, e))?
+                    .text()
+                    .await?;
+                verify_registry(&body, &signature, pubkey)?;
+                trace!("{} signature verified", url);
+                Some(signature)
+            }
+            None => None,
+        };
+
+        Ok(SourceCache {
+            body,
+            signature,
+            meta: RegistryMeta {
+                fetched_at: Some(unix_now()),
+                etag,
+                last_modified,
+                stale_since: None,
+            },
+        })
+    }
+
+    /// Loads an HTTP source, reusing the cache while it is younger than `ttl`
+    /// and falling back to it, flagged as stale, when the source is unreachable.
+    async fn load_http(
+        source: &RegistrySource,
+        url: &str,
+        cached: Option<SourceCache>,
+        ttl: u64,
+        force: bool,
+    ) -> Result<SourceCache> {
+        let cached = cached.filter(|cache| {
+            let valid = Self::cache_is_valid(source, cache);
+            if !valid {
+                warn!("Discarding cached {} without a valid signature", source.id);
+            }
+            valid
+        });
+        if let Some(cache) = &cached {
+            let fresh = cache
+                .meta
+                .fetched_at
+                .is_some_and(|fetched_at| unix_now().saturating_sub(fetched_at) < ttl);
+            if fresh && !force {
+                trace!("Using cached {}", source.id);
+                return Ok(cache.clone());
+            }
+        }
+
+        match Self::download(source, url, cached.as_ref()).await {
+            Ok(cache) => Ok(cache),
+            Err(e) => match cached {
+                Some(mut cache) => {
+                    warn!(
+                        "Failed to refresh {}, serving cached copy: {}",
+                        source.id, e
+                    );
+                    cache.meta.stale_since.get_or_insert_with(unix_now);
+                    Ok(cache)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn load_directory(dir: &Path) -> Result<Vec<Value>> {
+        let pattern = dir.join("*.json");
+        let mut paths: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())?
+            .filter_map(|path| path.ok())
+            .collect();
+        paths.sort();
+        let mut servers = vec![];
+        for path in paths {
+            let document = fs::read_to_string(&path)?;
+            let parsed = parse_servers(&document)
+                .with_context(|| format!("Invalid server file {}", path.to_string_lossy()))?;
+            servers.extend(parsed);
+        }
+        Ok(servers)
+    }
+
+    /// Loads every enabled source, merges them into `servers` and records
+    /// per-source status and id collisions. Returns whether any source
+    /// provided a catalog.
+    pub async fn detect(app_handle: &tauri::AppHandle, force: bool) -> Result<bool> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        let ttl = Self::ttl(&store);
+        let mut caches = Self::caches(&store);
+        let mut catalogs = vec![];
+        let mut statuses = vec![];
+
+        for source in Self::sources(&store) {
+            if !source.enabled {
+                continue;
+            }
+            let mut meta = RegistryMeta::default();
+            let loaded = match source.resolve() {
+                Ok(SourceLocation::Http(url)) => {
+                    let cached = caches.remove(&source.id);
+                    match Self::load_http(&source, &url, cached, ttl, force).await {
+                        Ok(cache) => {
+                            meta = cache.meta.clone();
+                            let servers = parse_servers(&cache.body);
+                            caches.insert(source.id.clone(), cache);
+                            servers
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                Ok(SourceLocation::File(path)) => fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.to_string_lossy()))
+                    .and_then(|document| parse_servers(&document)),
+                Ok(SourceLocation::Directory(dir)) => Self::load_directory(&dir),
+                Err(e) => Err(e),
+            };
+
+            let (servers, error) = match loaded {
+                Ok(servers) => (Some(servers), None),
+                Err(e) => {
+                    warn!("Failed to load registry source {}: {:#}", source.id, e);
+                    (None, Some(format!("{:#}", e)))
+                }
+            };
+            statuses.push(SourceStatus {
+                stale: meta.stale_since.is_some(),
+                servers: servers.as_ref().map_or(0, |servers| servers.len()),
+                source: source.clone(),
+                meta,
+                error,
+            });
+            if let Some(servers) = servers {
+                catalogs.push(Catalog {
+                    source_id: source.id,
+                    override_builtin: source.override_builtin,
+                    servers,
+                });
+            }
+        }
+
+        let available = !catalogs.is_empty();
+        let (merged, collisions) = merge_sources(catalogs, &Self::acknowledged_overrides(&store));
+        for collision in &collisions {
+            warn!(
+                "Server {} from {} is shadowed by {}{}",
+                collision.id,
+                collision.shadowed,
+                collision.source,
+                if collision.blocking {
+                    " until the override is acknowledged"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        // Superseded by the per-source `registry_cache`.
+        store.delete("servers_meta");
+        store.delete("servers_signature");
+        store.set("registry_cache", serde_json::to_value(caches)?);
+        store.set("registry_status", serde_json::to_value(statuses)?);
+        store.set("registry_collisions", serde_json::to_value(collisions)?);
+        if available {
+            store.set("servers", serde_json::to_string(&merged)?);
+        } else {
+            store.delete("servers");
+        }
+        Ok(available)
+    }
+
+    pub fn status(app_handle: &tauri::AppHandle) -> Result<RegistryStatus> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        Ok(RegistryStatus {
+            ttl_secs: Self::ttl(&store),
+            sources: store
+                .get("registry_status")
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default(),
+            collisions: store
+                .get("registry_collisions")
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn set_ttl(app_handle: &tauri::AppHandle, ttl_secs: u64) -> Result<()> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        store.set("registry_ttl_secs", ttl_secs);
+        Ok(())
+    }
+
+    pub fn get_sources(app_handle: &tauri::AppHandle) -> Result<Vec<RegistrySource>> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        Ok(Self::sources(&store))
+    }
+
+    /// Replaces the user-defined sources. The built-in registry cannot be
+    /// redefined and always has the lowest precedence.
+    pub fn set_sources(app_handle: &tauri::AppHandle, sources: Vec<RegistrySource>) -> Result<()> {
+        let mut ids = HashSet::new();
+        for source in &sources {
+            if source.id.is_empty() || source.id == DEFAULT_SOURCE_ID {
+                return Err(anyhow!("Invalid registry source id: {:?}", source.id));
+            }
+            if !ids.insert(source.id.as_str()) {
+                return Err(anyhow!("Duplicate registry source id: {}", source.id));
+            }
+            source.resolve()?;
+            if let Some(pubkey) = &source.pubkey {
+                PublicKey::from_base64(pubkey)
+                    .with_context(|| format!("Invalid pubkey for registry source {}", source.id))?;
+            }
+        }
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        store.set("registry_sources", serde_json::to_value(&sources)?);
+        Ok(())
+    }
+
+    /// Lets `source_id` replace the built-in server `server_id`, after the
+    /// user has seen the blocking collision, and reloads the registry.
+    pub async fn acknowledge_override(
+        app_handle: &tauri::AppHandle,
+        source_id: &str,
+        server_id: &str,
+    ) -> Result<()> {
+        let store = app_handle.store(APP_STATE_FILENAME)?;
+        let source = Self::user_sources(&store)
+            .into_iter()
+            .find(|source| source.id == source_id)
+            .ok_or_else(|| anyhow!("Unknown registry source: {}", source_id))?;
+        if !source.override_builtin {
+            return Err(anyhow!(
+                "Registry source {} may not override built-in servers",
+                source_id
+            ));
+        }
+        let mut acknowledged = Self::acknowledged_overrides(&store);
+        acknowledged.insert(override_key(source_id, server_id));
+        store.set(
+            "registry_override_acks",
+            serde_json::to_value(acknowledged)?,
+        );
+        Self::detect(app_handle, false).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Made with a test-only key pair from fixed seeds; run
+    /// `testdata/sign_registry_fixture.py` to regenerate these fixtures.
+    const TEST_PUBKEY: &str = "RWQBAgMEBQYHCHm1Vi6P5lT5QHixEuipi6eQH4U65pW+1+DjkQutBJZk";
+    const OTHER_PUBKEY: &str = "RWQREhMUFRYXGOfxYqEL7FWa/qGV5NzoS2lWjV0ssJY+tEbAaF4rF/Lw";
+    const TEST_BODY: &str = r#"[{"id":"filesystem","title":"Filesystem"}]"#;
+    const TEST_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCGfBio66mt40nZik+3nP6YCiglpSGUxHKbW3A9zkwWjDBxepk5BnKyPR2nsoVH8mHWb+ZHIkZjtjZgwqwIbv8wc=
+trusted comment: timestamp:1700000000\tfile:servers.json
+wYIeStaeB1YVBtc3kOfc2sMe8yMgo/pRoMErqGN1OTlKa13R3RzeNxGQkewj/OjtLrlvoAg119QfjUD4g3b9DQ==
+";
+
+    #[test]
+    fn accepts_good_signature() {
+        verify_registry(TEST_BODY, TEST_SIGNATURE, TEST_PUBKEY).unwrap();
+    }
+
+    #[test]
+    fn rejects_tampered_body() {
+        let tampered = TEST_BODY.replace("Filesystem", "Filesystem2");
+        assert!(verify_registry(&tampered, TEST_SIGNATURE, TEST_PUBKEY).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        assert!(verify_registry(TEST_BODY, TEST_SIGNATURE, OTHER_PUBKEY).is_err());
+    }
+
+    #[test]
+    fn rejects_garbage_signature() {
+        assert!(verify_registry(TEST_BODY, "not a signature", TEST_PUBKEY).is_err());
+    }
+
+    fn source(location: &str, pubkey: Option<&str>) -> RegistrySource {
+        RegistrySource {
+            id: "team".to_string(),
+            location: location.to_string(),
+            pubkey: pubkey.map(String::from),
+            enabled: true,
+            override_builtin: false,
+        }
+    }
+
+    #[test]
+    fn rejects_insecure_and_unsigned_remote_sources() {
+        assert!(source("http://example.com/servers.json", Some(TEST_PUBKEY))
+            .resolve()
+            .is_err());
+        assert!(source("HTTP://example.com/servers.json", Some(TEST_PUBKEY))
+            .resolve()
+            .is_err());
+        assert!(source("https://example.com/servers.json", None)
+            .resolve()
+            .is_err());
+        assert!(matches!(
+            source("https://example.com/servers.json", Some(TEST_PUBKEY)).resolve(),
+            Ok(SourceLocation::Http(_))
+        ));
+    }
+
+    fn catalog(source_id: &str, override_builtin: bool, command: &str) -> Catalog {
+        Catalog {
+            source_id: source_id.to_string(),
+            override_builtin,
+            servers: vec![serde_json::json!({ "id": "filesystem", "command": command })],
+        }
+    }
+
+    #[test]
+    fn builtin_servers_win_without_override() {
+        let (merged, collisions) = merge_sources(
+            vec![
+                catalog("team", false, "evil"),
+                catalog(DEFAULT_SOURCE_ID, false, "good"),
+            ],
+            &HashSet::new(),
+        );
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0]["command"], "good");
+        assert_eq!(collisions.len(), 1);
+        assert!(!collisions[0].blocking);
+    }
+
+    #[test]
+    fn unacknowledged_override_is_blocking() {
+        let (merged, collisions) = merge_sources(
+            vec![
+                catalog("team", true, "custom"),
+                catalog(DEFAULT_SOURCE_ID, false, "good"),
+            ],
+            &HashSet::new(),
+        );
+        assert_eq!(merged[0]["command"], "good");
+        assert!(collisions[0].blocking);
+        assert_eq!(collisions[0].shadowed, "team");
+    }
+
+    #[test]
+    fn acknowledged_override_replaces_builtin_server() {
+        let acknowledged = HashSet::from([override_key("team", "filesystem")]);
+        let (merged, collisions) = merge_sources(
+            vec![
+                catalog("team", true, "custom"),
+                catalog(DEFAULT_SOURCE_ID, false, "good"),
+            ],
+            &acknowledged,
+        );
+        assert_eq!(merged[0]["command"], "custom");
+        assert_eq!(merged[0]["source"], "team");
+        assert_eq!(collisions[0].source, "team");
+        assert_eq!(collisions[0].shadowed, DEFAULT_SOURCE_ID);
+        assert!(!collisions[0].blocking);
+    }
+}
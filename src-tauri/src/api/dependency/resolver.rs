@@ -0,0 +1,288 @@
+use super::core::{activate_managed_version, managed_versions, Dependency};
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use crate::utils::os::detect_shell;
+use crate::utils::version::Version;
+use crate::APP_STATE_FILENAME;
+use anyhow::{anyhow, Result};
+use log::{trace, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::Wry;
+use tauri_plugin_store::{Store, StoreExt};
+use tokio::process::Command;
+
+/// How long the user's shell may take to list the executables on `PATH` or
+/// print it. Interactive shells run the user's profile, which can hang.
+const SHELL_LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Dependency {
+    /// Executables a directory must contain to count as an install.
+    fn required_tools(&self) -> &'static [&'static str] {
+        match self {
+            Dependency::Node => &["node", "npx"],
+            Dependency::Uv => &["uv", "uvx"],
+            Dependency::Bun => &["bun"],
+            Dependency::Deno => &["deno"],
+        }
+    }
+}
+
+pub(crate) fn executable_name(tool: &str) -> String {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        tool.to_string()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        match tool {
+            "npm" | "npx" => format!("{}.cmd", tool),
+            _ => format!("{}.exe", tool),
+        }
+    }
+}
+
+/// The directory holding the runtime's executables. Older versions stored the
+/// executable itself (raw `which` output) instead of its directory.
+pub(crate) fn tool_dir(path: &str) -> PathBuf {
+    let path = PathBuf::from(path.lines().next().unwrap_or_default().trim());
+    if path.is_file() {
+        path.parent().map(Path::to_path_buf).unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+/// Whether `dir` contains every executable the runtime needs.
+pub fn is_valid_dir(dir: &Path, dependency: Dependency) -> bool {
+    dependency
+        .required_tools()
+        .iter()
+        .all(|tool| dir.join(executable_name(tool)).is_file())
+}
+
+/// Finds the executables inside an unpacked archive: `root` itself, its `bin`
+/// directory, or the same one level down, visiting subdirectories by name.
+pub fn find_tool_dir(root: &Path, dependency: Dependency) -> Option<PathBuf> {
+    let mut candidates = vec![root.to_path_buf(), root.join("bin")];
+    if let Ok(entries) = fs::read_dir(root) {
+        let mut children: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        children.sort();
+        for child in children {
+            candidates.push(child.join("bin"));
+            candidates.push(child);
+        }
+    }
+    candidates
+        .into_iter()
+        .find(|dir| is_valid_dir(dir, dependency))
+}
+
+/// Runs a lookup command, giving up after [`SHELL_LOOKUP_TIMEOUT`].
+async fn lookup_output(mut command: Command) -> Result<String> {
+    let output = tokio::time::timeout(
+        SHELL_LOOKUP_TIMEOUT,
+        command
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow!("lookup timed out"))??;
+    if !output.status.success() {
+        return Err(anyhow!("lookup failed: {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// The `PATH` the user's login shell sets up, falling back to Magnet's own
+/// when the shell cannot be asked. GUI apps on macOS and Linux start with a
+/// minimal `PATH`; Windows apps already get the full one.
+pub(crate) async fn login_shell_path() -> Option<String> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        // Interactive shells may print banners; mark the line holding `PATH`.
+        const MARKER: &str = "MAGNET_PATH=";
+        let output = async {
+            let mut command = Command::new(detect_shell()?);
+            command
+                .arg("-ic")
+                .arg(format!("printf '\\n{}%s\\n' \"$PATH\"", MARKER));
+            lookup_output(command).await
+        }
+        .await;
+        match output {
+            Ok(output) => {
+                if let Some(path) = output
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix(MARKER))
+                    .filter(|path| !path.is_empty())
+                {
+                    return Some(path.to_string());
+                }
+            }
+            Err(e) => trace!("No login shell PATH: {}", e),
+        }
+    }
+    std::env::var("PATH").ok()
+}
+
+/// Every `tool` executable on the user's `PATH`, in `PATH` order. Asks the
+/// user's shell so the `PATH` from their profile is used rather than the
+/// minimal one a GUI app is started with.
+pub(crate) async fn find_executables(tool: &str) -> Vec<PathBuf> {
+    let output = async {
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        let command = {
+            let mut command = Command::new(detect_shell()?);
+            command.arg("-ic").arg(format!("which -a {}", tool));
+            command
+        };
+        #[cfg(target_os = "windows")]
+        let command = {
+            let mut command = Command::new("where.exe");
+            command.arg(tool);
+            command
+        };
+        lookup_output(command).await
+    }
+    .await;
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            trace!("No system {}: {}", tool, e);
+            return vec![];
+        }
+    };
+    trace!("{} lookup output: {}", tool, output);
+
+    let mut executables: Vec<PathBuf> = vec![];
+    // Interactive shells may print banners; keep only lines naming an executable.
+    for line in output.lines().map(str::trim) {
+        let path = PathBuf::from(line);
+        if path.is_absolute() && path.is_file() && !executables.contains(&path) {
+            executables.push(path);
+        }
+    }
+    executables
+}
+
+/// Directories on the user's `PATH` that contain the runtime, in `PATH` order.
+async fn system_candidates(dependency: Dependency) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = vec![];
+    for executable in find_executables(dependency.required_tools()[0]).await {
+        if let Some(dir) = executable.parent() {
+            if !dirs.iter().any(|known| known == dir) {
+                dirs.push(dir.to_path_buf());
+            }
+        }
+    }
+    dirs
+}
+
+/// Managed installs, newest first.
+fn managed_candidates(store: &Store<Wry>, dependency: Dependency) -> Vec<(String, PathBuf)> {
+    let mut installs: Vec<(String, PathBuf)> = managed_versions(store, dependency)
+        .into_iter()
+        .map(|(version, path)| (version, PathBuf::from(path)))
+        .collect();
+    installs.sort_by_key(|(version, _)| std::cmp::Reverse(Version::parse(version)));
+    installs
+}
+
+/// Makes the first directory on the user's `PATH` holding the runtime the one
+/// in use.
+async fn use_system_install(store: &Store<Wry>, dependency: Dependency) -> Option<PathBuf> {
+    let dir = system_candidates(dependency)
+        .await
+        .into_iter()
+        .find(|dir| is_valid_dir(dir, dependency))?;
+    trace!(
+        "Using system {} at {}",
+        dependency.display_name(),
+        dir.to_string_lossy()
+    );
+    store.set(dependency.path_key(), dir.to_string_lossy().to_string());
+    store.set(dependency.use_system_key(), true);
+    Some(dir)
+}
+
+/// Makes sure the stored runtime directory still contains working executables
+/// and falls back to another install when it does not: the newest managed
+/// version or the first match on `PATH`, trying the kind that was in use
+/// first. Returns the directory in use, or `None` when there is none.
+pub async fn resolve(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+) -> Result<Option<PathBuf>> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let use_system = store
+        .get(dependency.use_system_key())
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    if let Some(stored) = store
+        .get(dependency.path_key())
+        .and_then(|v| v.as_str().map(String::from))
+    {
+        let dir = tool_dir(&stored);
+        if is_valid_dir(&dir, dependency) {
+            trace!(
+                "{} found at {}",
+                dependency.display_name(),
+                dir.to_string_lossy()
+            );
+            let dir_str = dir.to_string_lossy().to_string();
+            if dir_str != stored {
+                store.set(dependency.path_key(), dir_str);
+            }
+            return Ok(Some(dir));
+        }
+        warn!(
+            "{} is no longer available at {}",
+            dependency.display_name(),
+            stored
+        );
+    }
+
+    let use_managed = || -> Result<Option<PathBuf>> {
+        let Some((version, dir)) = managed_candidates(&store, dependency)
+            .into_iter()
+            .find(|(_, dir)| is_valid_dir(dir, dependency))
+        else {
+            return Ok(None);
+        };
+        trace!("Using managed {} {}", dependency.display_name(), version);
+        activate_managed_version(
+            &store,
+            dependency,
+            &version,
+            dir.to_string_lossy().to_string(),
+        )?;
+        Ok(Some(dir))
+    };
+    let resolved = if use_system {
+        match use_system_install(&store, dependency).await {
+            Some(dir) => Some(dir),
+            None => use_managed()?,
+        }
+    } else {
+        match use_managed()? {
+            Some(dir) => Some(dir),
+            None => use_system_install(&store, dependency).await,
+        }
+    };
+    if resolved.is_some() {
+        return Ok(resolved);
+    }
+
+    trace!("{} not found", dependency.display_name());
+    store.delete(dependency.path_key());
+    store.set(dependency.use_system_key(), false);
+    Ok(None)
+}
@@ -0,0 +1,206 @@
+use super::core::Dependency;
+use super::resolver::{executable_name, tool_dir};
+use crate::utils::version::Version;
+use crate::APP_STATE_FILENAME;
+use anyhow::Result;
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri_plugin_store::StoreExt;
+use tokio::process::Command;
+
+/// How long a single `--version` probe may take before it counts as broken.
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Where the runtime servers are launched with comes from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeSource {
+    /// Found on the user's `PATH`.
+    System,
+    /// Installed by Magnet.
+    Managed,
+}
+
+/// Result of running one executable of a runtime with `--version`.
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolStatus {
+    name: String,
+    path: String,
+    version: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RuntimeStatus {
+    /// Whether the main executable (`node`, `uv`) runs.
+    installed: bool,
+    path: Option<String>,
+    source: Option<RuntimeSource>,
+    version: Option<String>,
+    #[serde(rename = "minimumVersion")]
+    minimum_version: String,
+    tools: Vec<ToolStatus>,
+    /// Human readable descriptions of everything that looks wrong.
+    problems: Vec<String>,
+}
+
+impl RuntimeStatus {
+    pub fn version(&self) -> Option<Version> {
+        self.version.as_deref().and_then(Version::parse)
+    }
+
+    /// Whether every executable of the runtime ran successfully.
+    pub fn is_healthy(&self) -> bool {
+        self.installed && self.tools.iter().all(|tool| tool.error.is_none())
+    }
+}
+
+impl Dependency {
+    /// Executables shipped with the runtime; the first one is the runtime itself.
+    fn tools(&self) -> &'static [&'static str] {
+        match self {
+            Dependency::Node => &["node", "npm", "npx"],
+            Dependency::Uv => &["uv", "uvx"],
+            Dependency::Bun => &["bun"],
+            Dependency::Deno => &["deno"],
+        }
+    }
+
+    /// Oldest release Magnet's own server configurations are known to work with.
+    pub fn minimum_version(&self) -> Version {
+        match self {
+            Dependency::Node => Version::new(18, 0, 0),
+            Dependency::Uv => Version::new(0, 4, 0),
+            Dependency::Bun => Version::new(1, 0, 0),
+            // First release able to run `npm:` specifiers.
+            Dependency::Deno => Version::new(1, 28, 0),
+        }
+    }
+}
+
+/// Runs `<dir>/<tool> --version` with `dir` first on `PATH`, so npm's and
+/// npx's `#!/usr/bin/env node` resolve to the same Node.js.
+async fn probe(dir: &Path, tool: &str) -> ToolStatus {
+    let executable = dir.join(executable_name(tool));
+    let mut status = ToolStatus {
+        name: tool.to_string(),
+        path: executable.to_string_lossy().to_string(),
+        version: None,
+        error: None,
+    };
+    if !executable.is_file() {
+        status.error = Some(format!("{} not found", status.path));
+        return status;
+    }
+
+    let mut paths = vec![dir.to_path_buf()];
+    if let Some(path) = std::env::var_os("PATH") {
+        paths.extend(std::env::split_paths(&path));
+    }
+    let mut command = Command::new(&executable);
+    command
+        .arg("--version")
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    if let Ok(path) = std::env::join_paths(paths) {
+        command.env("PATH", path);
+    }
+
+    match tokio::time::timeout(VERSION_PROBE_TIMEOUT, command.output()).await {
+        Err(_) => status.error = Some(format!("{} --version timed out", tool)),
+        Ok(Err(e)) => status.error = Some(format!("Failed to run {}: {}", tool, e)),
+        Ok(Ok(output)) if !output.status.success() => {
+            status.error = Some(format!(
+                "{} --version exited with {}: {}",
+                tool,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match Version::parse(&stdout) {
+                Some(version) => status.version = Some(version.to_string()),
+                None => {
+                    status.error = Some(format!(
+                        "Unrecognized {} version output: {}",
+                        tool,
+                        stdout.trim()
+                    ))
+                }
+            }
+        }
+    }
+    trace!("{:?}", status);
+    status
+}
+
+/// Inspects the runtime recorded in the store: runs every executable it ships
+/// with `--version` and lists anything that would keep servers from starting.
+pub async fn runtime_status(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+) -> Result<RuntimeStatus> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let path = store
+        .get(dependency.path_key())
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|path| !path.trim().is_empty());
+    let use_system = store
+        .get(dependency.use_system_key())
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let minimum = dependency.minimum_version();
+
+    let mut status = RuntimeStatus {
+        installed: false,
+        path: path.clone(),
+        source: path.as_ref().map(|_| match use_system {
+            true => RuntimeSource::System,
+            false => RuntimeSource::Managed,
+        }),
+        version: None,
+        minimum_version: minimum.to_string(),
+        tools: vec![],
+        problems: vec![],
+    };
+    let Some(path) = path else {
+        status
+            .problems
+            .push(format!("{} was not found", dependency.display_name()));
+        return Ok(status);
+    };
+
+    let dir = tool_dir(&path);
+    if !dir.is_dir() {
+        status
+            .problems
+            .push(format!("{} does not exist", dir.to_string_lossy()));
+        return Ok(status);
+    }
+    for tool in dependency.tools() {
+        let tool_status = probe(&dir, tool).await;
+        if let Some(error) = &tool_status.error {
+            status.problems.push(error.clone());
+        }
+        status.tools.push(tool_status);
+    }
+
+    let runtime = &status.tools[0];
+    status.installed = runtime.error.is_none();
+    status.version = runtime.version.clone();
+    if let Some(version) = status.version() {
+        if version < minimum {
+            status.problems.push(format!(
+                "{} {} is older than the minimum supported {}",
+                dependency.display_name(),
+                version,
+                minimum
+            ));
+        }
+    }
+    Ok(status)
+}
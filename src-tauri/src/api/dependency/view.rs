@@ -1,42 +1,222 @@
 use log::debug;
-use serde::{Deserialize, Serialize};
-use tauri_plugin_store::StoreExt;
+use serde::Serialize;
 
-use super::core::{NpmHandler, ResourceHandler, UVHandler};
+use super::container::{ContainerEngine, ContainerHandler};
+use super::core::{
+    available_versions, switch_version, BunHandler, DenoHandler, Dependency, DependencyVersions,
+    InstallState, NpmHandler, UVHandler,
+};
+use super::maintenance::{repair, uninstall, UninstallReport};
+use super::registry::{RegistryCollision, RegistrySource, RegistryStatus, ResourceHandler};
+use super::runtime::{runtime_status, RuntimeStatus};
+use crate::error::MagnetResult;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 pub struct DependencyStatus {
-    uv: bool,
-    node: bool,
+    uv: RuntimeStatus,
+    node: RuntimeStatus,
+    bun: RuntimeStatus,
+    deno: RuntimeStatus,
+    container: Option<ContainerEngine>,
     servers: bool,
+    /// Replacements of built-in servers waiting to be acknowledged.
+    #[serde(rename = "registryOverrides")]
+    registry_overrides: Vec<RegistryCollision>,
 }
 
 #[tauri::command]
-pub async fn check_dependency(app_handle: tauri::AppHandle) -> DependencyStatus {
-    let status = DependencyStatus {
-        uv: UVHandler::detect(&app_handle).await.unwrap_or(false),
-        node: NpmHandler::detect(&app_handle).await.unwrap_or(false),
-        servers: ResourceHandler::detect(&app_handle).await.unwrap_or(false),
-    };
-    status
+pub async fn check_dependency(app_handle: tauri::AppHandle) -> MagnetResult<DependencyStatus> {
+    if let Err(e) = UVHandler::detect(&app_handle).await {
+        debug!("uv detection failed: {}", e);
+    }
+    if let Err(e) = NpmHandler::detect(&app_handle).await {
+        debug!("Node.js detection failed: {}", e);
+    }
+    if let Err(e) = BunHandler::detect(&app_handle).await {
+        debug!("Bun detection failed: {}", e);
+    }
+    if let Err(e) = DenoHandler::detect(&app_handle).await {
+        debug!("Deno detection failed: {}", e);
+    }
+    Ok(DependencyStatus {
+        uv: runtime_status(&app_handle, Dependency::Uv).await?,
+        node: runtime_status(&app_handle, Dependency::Node).await?,
+        bun: runtime_status(&app_handle, Dependency::Bun).await?,
+        deno: runtime_status(&app_handle, Dependency::Deno).await?,
+        container: ContainerHandler::detect(&app_handle).await?,
+        servers: ResourceHandler::detect(&app_handle, false)
+            .await
+            .unwrap_or(false),
+        registry_overrides: ResourceHandler::status(&app_handle)?.blocking_collisions(),
+    })
 }
 
 #[tauri::command]
-pub async fn install_npm(app_handle: tauri::AppHandle) -> Result<(), String> {
-    NpmHandler::install(&app_handle)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn install_npm(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+) -> MagnetResult<String> {
+    Ok(NpmHandler::install(&app_handle, version.as_deref()).await?)
 }
 
 #[tauri::command]
-pub async fn install_uv(app_handle: tauri::AppHandle) -> Result<(), String> {
-    UVHandler::install(&app_handle)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn install_uv(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+) -> MagnetResult<String> {
+    Ok(UVHandler::install(&app_handle, version.as_deref()).await?)
+}
+
+#[tauri::command]
+pub async fn install_bun(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+) -> MagnetResult<String> {
+    Ok(BunHandler::install(&app_handle, version.as_deref()).await?)
+}
+
+#[tauri::command]
+pub async fn install_deno(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+) -> MagnetResult<String> {
+    Ok(DenoHandler::install(&app_handle, version.as_deref()).await?)
+}
+
+#[tauri::command]
+pub async fn uninstall_node(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> MagnetResult<UninstallReport> {
+    Ok(uninstall(
+        &app_handle,
+        Dependency::Node,
+        version.as_deref(),
+        force.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub async fn uninstall_uv(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> MagnetResult<UninstallReport> {
+    Ok(uninstall(
+        &app_handle,
+        Dependency::Uv,
+        version.as_deref(),
+        force.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub async fn uninstall_bun(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> MagnetResult<UninstallReport> {
+    Ok(uninstall(
+        &app_handle,
+        Dependency::Bun,
+        version.as_deref(),
+        force.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub async fn uninstall_deno(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> MagnetResult<UninstallReport> {
+    Ok(uninstall(
+        &app_handle,
+        Dependency::Deno,
+        version.as_deref(),
+        force.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub async fn repair_dependency(
+    app_handle: tauri::AppHandle,
+    dependency: Dependency,
+) -> MagnetResult<RuntimeStatus> {
+    Ok(repair(&app_handle, dependency).await?)
+}
+
+#[tauri::command]
+pub async fn list_dependency_versions(
+    app_handle: tauri::AppHandle,
+    dependency: Dependency,
+) -> MagnetResult<DependencyVersions> {
+    Ok(available_versions(&app_handle, dependency).await?)
+}
+
+#[tauri::command]
+pub async fn switch_dependency_version(
+    app_handle: tauri::AppHandle,
+    dependency: Dependency,
+    version: String,
+) -> MagnetResult<()> {
+    Ok(switch_version(&app_handle, dependency, &version)?)
+}
+
+/// Stops a running install of `dependency`. Returns whether one was running.
+#[tauri::command]
+pub async fn cancel_install(
+    install_state: tauri::State<'_, InstallState>,
+    dependency: Dependency,
+) -> MagnetResult<bool> {
+    Ok(install_state.cancel(dependency))
 }
 
 #[tauri::command]
-pub async fn check_resource(app_handle: tauri::AppHandle) -> bool {
+pub async fn check_resource(app_handle: tauri::AppHandle, force: Option<bool>) -> bool {
     debug!("Start check_resource in backend");
-    ResourceHandler::detect(&app_handle).await.unwrap_or(false)
+    ResourceHandler::detect(&app_handle, force.unwrap_or(false))
+        .await
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub async fn get_registry_status(app_handle: tauri::AppHandle) -> MagnetResult<RegistryStatus> {
+    Ok(ResourceHandler::status(&app_handle)?)
+}
+
+#[tauri::command]
+pub async fn set_registry_ttl(app_handle: tauri::AppHandle, ttl_secs: u64) -> MagnetResult<()> {
+    Ok(ResourceHandler::set_ttl(&app_handle, ttl_secs)?)
+}
+
+#[tauri::command]
+pub async fn get_registry_sources(
+    app_handle: tauri::AppHandle,
+) -> MagnetResult<Vec<RegistrySource>> {
+    Ok(ResourceHandler::get_sources(&app_handle)?)
+}
+
+#[tauri::command]
+pub async fn set_registry_sources(
+    app_handle: tauri::AppHandle,
+    sources: Vec<RegistrySource>,
+) -> MagnetResult<()> {
+    Ok(ResourceHandler::set_sources(&app_handle, sources)?)
+}
+
+/// Accepts a blocking collision: the server from `source_id` replaces the
+/// built-in one with the same id.
+#[tauri::command]
+pub async fn acknowledge_registry_override(
+    app_handle: tauri::AppHandle,
+    source_id: &str,
+    server_id: &str,
+) -> MagnetResult<()> {
+    Ok(ResourceHandler::acknowledge_override(&app_handle, source_id, server_id).await?)
 }
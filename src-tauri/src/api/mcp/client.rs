@@ -0,0 +1,300 @@
+use crate::api::clients::core::ClientServerConfig;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::task::JoinHandle;
+
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Upper bound on captured stderr so a chatty server cannot exhaust memory.
+const MAX_STDERR_BYTES: usize = 64 * 1024;
+/// Upper bound on pages fetched for a single `*/list` call.
+const MAX_LIST_PAGES: usize = 50;
+/// How long `close` waits for the rest of stderr, in case a process the server
+/// started keeps the pipe open.
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A JSON-RPC connection to an MCP server spawned over stdio.
+pub struct McpSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    stderr: Arc<Mutex<String>>,
+    stderr_reader: JoinHandle<()>,
+    next_id: u64,
+}
+
+/// What the server reported about itself during `initialize`.
+#[derive(Debug, Clone)]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    pub server_info: Value,
+    pub capabilities: Value,
+    pub instructions: Option<String>,
+}
+
+impl McpSession {
+    /// Starts the server exactly as the client config describes it.
+    pub fn spawn(server_config: &ClientServerConfig) -> Result<Self> {
+        trace!(
+            "Spawning MCP server: {} {:?}",
+            server_config.command,
+            server_config.args
+        );
+        let mut child = Command::new(&server_config.command)
+            .args(&server_config.args)
+            .envs(&server_config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", server_config.command))?;
+
+        let stdin = child.stdin.take().context("Failed to open server stdin")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to open server stdout")?;
+        let stderr_pipe = child
+            .stderr
+            .take()
+            .context("Failed to open server stderr")?;
+
+        let stderr = Arc::new(Mutex::new(String::new()));
+        let captured = stderr.clone();
+        let stderr_reader = tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr_pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let mut captured = captured.lock().unwrap();
+                if captured.len() + line.len() < MAX_STDERR_BYTES {
+                    captured.push_str(&line);
+                    captured.push('\n');
+                }
+            }
+        });
+
+        Ok(McpSession {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            stderr,
+            stderr_reader,
+            next_id: 1,
+        })
+    }
+
+    /// Performs the `initialize` handshake and sends `notifications/initialized`.
+    pub async fn initialize(&mut self) -> Result<InitializeResult> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "magnet-desktop",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        self.notify("notifications/initialized", json!({})).await?;
+
+        Ok(InitializeResult {
+            protocol_version: result["protocolVersion"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            server_info: result["serverInfo"].clone(),
+            capabilities: result["capabilities"].clone(),
+            instructions: result["instructions"].as_str().map(String::from),
+        })
+    }
+
+    /// Calls a paginated `*/list` method and concatenates every page's `key` array.
+    pub async fn list(&mut self, method: &str, key: &str) -> Result<Vec<Value>> {
+        let mut items = vec![];
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let mut result = self.request(method, params).await?;
+            if let Some(page) = result[key].as_array_mut() {
+                items.append(page);
+            }
+            cursor = result["nextCursor"].as_str().map(String::from);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Sends a request and waits for its response, answering any requests the
+    /// server makes in the meantime.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))
+        .await?;
+
+        loop {
+            let message = self.receive().await?;
+            if message.get("method").is_some() {
+                if let Some(request_id) = message.get("id") {
+                    self.reply_to_server_request(request_id.clone(), &message["method"])
+                        .await?;
+                }
+                continue;
+            }
+            if message["id"].as_u64() != Some(id) {
+                debug!("Ignoring unexpected response: {}", message);
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(anyhow!(
+                    "{} failed: {} (code {})",
+                    method,
+                    error["message"].as_str().unwrap_or("unknown error"),
+                    error["code"]
+                ));
+            }
+            return Ok(message["result"].clone());
+        }
+    }
+
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+        .await
+    }
+
+    /// Everything the server has written to stderr so far.
+    pub fn stderr(&self) -> String {
+        self.stderr.lock().unwrap().clone()
+    }
+
+    /// Stops the server and returns everything it wrote to stderr, including
+    /// output still in the pipe.
+    pub async fn close(mut self) -> String {
+        let _ = self.child.kill().await;
+        let _ = tokio::time::timeout(STDERR_DRAIN_TIMEOUT, &mut self.stderr_reader).await;
+        self.stderr()
+    }
+
+    async fn reply_to_server_request(&mut self, id: Value, method: &Value) -> Result<()> {
+        // Magnet advertises no client capabilities, so only `ping` is answered.
+        let reply = if method.as_str() == Some("ping") {
+            json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+        } else {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": "Method not found" },
+            })
+        };
+        self.send(&reply).await
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        trace!("MCP -> {}", line);
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .context("Failed to write to server stdin")?;
+        self.stdin.flush().await?;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Value> {
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await?
+                .ok_or_else(|| anyhow!("Server closed its output before responding"))?;
+            trace!("MCP <- {}", line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(&line) {
+                Ok(message) => return Ok(message),
+                // Servers sometimes log to stdout; skip anything that is not JSON-RPC.
+                Err(_) => debug!("Ignoring non JSON-RPC output: {}", line),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::api::servers::args::InputArg;
+    use std::collections::HashMap;
+
+    /// Answers `initialize` and `tools/list` like a minimal MCP server.
+    const STUB_SERVER: &str = r#"
+read -r request
+echo "stub starting" >&2
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","serverInfo":{"name":"stub","version":"1.0.0"},"capabilities":{"tools":{}},"instructions":"Say hi"}}'
+read -r notification
+read -r request
+echo 'not json-rpc'
+echo "stub done" >&2
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","inputSchema":{"type":"object"}}]}}'
+"#;
+
+    fn stub_config() -> ClientServerConfig {
+        ClientServerConfig {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), STUB_SERVER.to_string()],
+            env: HashMap::new(),
+            command_creator: String::new(),
+            input_arg: InputArg::default(),
+            input_args: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn handshake_with_stub_server() {
+        let mut session = McpSession::spawn(&stub_config()).unwrap();
+        let initialized = session.initialize().await.unwrap();
+        assert_eq!(initialized.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(initialized.server_info["name"], "stub");
+        assert!(initialized.capabilities.get("tools").is_some());
+        assert_eq!(initialized.instructions.as_deref(), Some("Say hi"));
+
+        let tools = session.list("tools/list", "tools").await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "echo");
+
+        let stderr = session.close().await;
+        assert_eq!(stderr, "stub starting\nstub done\n");
+    }
+
+    #[tokio::test]
+    async fn reports_server_exiting_early() {
+        let mut config = stub_config();
+        config.args = vec!["-c".to_string(), "echo boom >&2; exit 1".to_string()];
+        let mut session = McpSession::spawn(&config).unwrap();
+        assert!(session.initialize().await.is_err());
+        assert_eq!(session.close().await, "boom\n");
+    }
+}
@@ -0,0 +1,341 @@
+use super::client::{InitializeResult, McpSession};
+use crate::api::clients::core::{ClientServerConfig, McpClient};
+use crate::api::servers::core::get_client_server_config;
+use crate::error::{MagnetError, MagnetResult};
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TEST_TIMEOUT_SECS: u64 = 60;
+/// How long an inspector request may take before it is abandoned.
+const INSPECTOR_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Serialize)]
+pub struct StepTiming {
+    step: String,
+    #[serde(rename = "elapsedMs")]
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ServerTestReport {
+    success: bool,
+    #[serde(rename = "protocolVersion")]
+    protocol_version: Option<String>,
+    #[serde(rename = "serverInfo")]
+    server_info: Option<Value>,
+    capabilities: Option<Value>,
+    instructions: Option<String>,
+    tools: Option<Vec<Value>>,
+    resources: Option<Vec<Value>>,
+    prompts: Option<Vec<Value>>,
+    timings: Vec<StepTiming>,
+    #[serde(rename = "elapsedMs")]
+    elapsed_ms: u64,
+    error: Option<String>,
+    stderr: String,
+}
+
+pub async fn find_server_config(
+    server_id: &str,
+    client: McpClient,
+) -> MagnetResult<ClientServerConfig> {
+    get_client_server_config(client)
+        .await?
+        .remove(server_id)
+        .ok_or_else(|| MagnetError::UnknownServer(server_id.to_string()))
+}
+
+/// Runs `step` with what is left of the overall deadline and records how long it took.
+async fn timed<T>(
+    report: &mut ServerTestReport,
+    step: &str,
+    deadline: Instant,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let started = Instant::now();
+    let remaining = deadline.saturating_duration_since(started);
+    let result = tokio::time::timeout(remaining, future)
+        .await
+        .map_err(|_| anyhow!("Timed out during {}", step))?;
+    report.timings.push(StepTiming {
+        step: step.to_string(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    });
+    result
+}
+
+async fn run_test(
+    session: &mut McpSession,
+    report: &mut ServerTestReport,
+    deadline: Instant,
+) -> Result<()> {
+    let initialized = timed(report, "initialize", deadline, session.initialize()).await?;
+    let capabilities = initialized.capabilities.clone();
+    report.protocol_version = Some(initialized.protocol_version);
+    report.server_info = Some(initialized.server_info);
+    report.capabilities = Some(initialized.capabilities);
+    report.instructions = initialized.instructions;
+
+    if capabilities.get("tools").is_some() {
+        let tools = timed(
+            report,
+            "tools/list",
+            deadline,
+            session.list("tools/list", "tools"),
+        );
+        report.tools = Some(tools.await?);
+    }
+    if capabilities.get("resources").is_some() {
+        let resources = timed(
+            report,
+            "resources/list",
+            deadline,
+            session.list("resources/list", "resources"),
+        );
+        report.resources = Some(resources.await?);
+    }
+    if capabilities.get("prompts").is_some() {
+        let prompts = timed(
+            report,
+            "prompts/list",
+            deadline,
+            session.list("prompts/list", "prompts"),
+        );
+        report.prompts = Some(prompts.await?);
+    }
+    Ok(())
+}
+
+/// Spawns the server from the client's config, performs the MCP handshake and
+/// lists what it offers. Failures of the server itself are reported in the
+/// returned report rather than as an error.
+pub async fn test_server_function(
+    server_id: &str,
+    client: McpClient,
+    timeout: Duration,
+) -> MagnetResult<ServerTestReport> {
+    let server_config = find_server_config(server_id, client).await?;
+    let started = Instant::now();
+    let deadline = started + timeout;
+    let mut report = ServerTestReport::default();
+
+    let mut session = match McpSession::spawn(&server_config) {
+        Ok(session) => session,
+        Err(e) => {
+            report.error = Some(format!("{:#}", e));
+            return Ok(report);
+        }
+    };
+    let outcome = run_test(&mut session, &mut report, deadline).await;
+    report.elapsed_ms = started.elapsed().as_millis() as u64;
+    report.stderr = session.close().await;
+
+    match outcome {
+        Ok(()) => report.success = true,
+        Err(e) => {
+            debug!("Server {} failed its test: {:#}", server_id, e);
+            report.error = Some(format!("{:#}", e));
+        }
+    }
+    Ok(report)
+}
+
+/// Open inspector sessions, managed as Tauri state and keyed by session id.
+#[derive(Default)]
+pub struct InspectorState {
+    sessions: Mutex<HashMap<String, Arc<tokio::sync::Mutex<McpSession>>>>,
+    next_session_id: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectorSession {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+    #[serde(rename = "serverId")]
+    server_id: String,
+    #[serde(rename = "protocolVersion")]
+    protocol_version: String,
+    #[serde(rename = "serverInfo")]
+    server_info: Value,
+    capabilities: Value,
+    instructions: Option<String>,
+}
+
+impl InspectorState {
+    fn session(&self, session_id: &str) -> MagnetResult<Arc<tokio::sync::Mutex<McpSession>>> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| MagnetError::UnknownSession(session_id.to_string()))
+    }
+
+    /// Sends one request on the session, bounded by `INSPECTOR_REQUEST_TIMEOUT`.
+    async fn request(&self, session_id: &str, method: &str, params: Value) -> MagnetResult<Value> {
+        let session = self.session(session_id)?;
+        let mut session = session.lock().await;
+        let result =
+            tokio::time::timeout(INSPECTOR_REQUEST_TIMEOUT, session.request(method, params))
+                .await
+                .map_err(|_| MagnetError::Other(format!("Timed out waiting for {}", method)))??;
+        Ok(result)
+    }
+
+    async fn list(&self, session_id: &str, method: &str, key: &str) -> MagnetResult<Vec<Value>> {
+        let session = self.session(session_id)?;
+        let mut session = session.lock().await;
+        let items = tokio::time::timeout(INSPECTOR_REQUEST_TIMEOUT, session.list(method, key))
+            .await
+            .map_err(|_| MagnetError::Other(format!("Timed out waiting for {}", method)))??;
+        Ok(items)
+    }
+}
+
+/// Spawns an installed server and keeps the initialized connection open for
+/// interactive use.
+pub async fn open_inspector_session_function(
+    state: &InspectorState,
+    server_id: &str,
+    client: McpClient,
+    timeout: Duration,
+) -> MagnetResult<InspectorSession> {
+    let server_config = find_server_config(server_id, client).await?;
+    let mut session = McpSession::spawn(&server_config)?;
+    let initialized: InitializeResult =
+        match tokio::time::timeout(timeout, session.initialize()).await {
+            Ok(Ok(initialized)) => initialized,
+            Ok(Err(e)) => {
+                let stderr = session.close().await;
+                return Err(MagnetError::Other(format!("{:#}\n{}", e, stderr)));
+            }
+            Err(_) => {
+                let stderr = session.close().await;
+                return Err(MagnetError::Other(format!(
+                    "Timed out during initialize\n{}",
+                    stderr
+                )));
+            }
+        };
+
+    let session_id = format!(
+        "{}-{}",
+        server_id,
+        state.next_session_id.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    state.sessions.lock().unwrap().insert(
+        session_id.clone(),
+        Arc::new(tokio::sync::Mutex::new(session)),
+    );
+    debug!("Opened inspector session {}", session_id);
+
+    Ok(InspectorSession {
+        session_id,
+        server_id: server_id.to_string(),
+        protocol_version: initialized.protocol_version,
+        server_info: initialized.server_info,
+        capabilities: initialized.capabilities,
+        instructions: initialized.instructions,
+    })
+}
+
+pub async fn close_inspector_session_function(
+    state: &InspectorState,
+    session_id: &str,
+) -> MagnetResult<()> {
+    let session = state
+        .sessions
+        .lock()
+        .unwrap()
+        .remove(session_id)
+        .ok_or_else(|| MagnetError::UnknownSession(session_id.to_string()))?;
+    if let Ok(session) = Arc::try_unwrap(session) {
+        session.into_inner().close().await;
+    }
+    debug!("Closed inspector session {}", session_id);
+    Ok(())
+}
+
+/// Tools offered by the server, each with its `inputSchema`.
+pub async fn list_tools_function(
+    state: &InspectorState,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    state.list(session_id, "tools/list", "tools").await
+}
+
+pub async fn call_tool_function(
+    state: &InspectorState,
+    session_id: &str,
+    name: &str,
+    arguments: Value,
+) -> MagnetResult<Value> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    state
+        .request(
+            session_id,
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+}
+
+pub async fn list_resources_function(
+    state: &InspectorState,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    state.list(session_id, "resources/list", "resources").await
+}
+
+pub async fn read_resource_function(
+    state: &InspectorState,
+    session_id: &str,
+    uri: &str,
+) -> MagnetResult<Value> {
+    state
+        .request(session_id, "resources/read", json!({ "uri": uri }))
+        .await
+}
+
+pub async fn list_prompts_function(
+    state: &InspectorState,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    state.list(session_id, "prompts/list", "prompts").await
+}
+
+pub async fn get_prompt_function(
+    state: &InspectorState,
+    session_id: &str,
+    name: &str,
+    arguments: HashMap<String, String>,
+) -> MagnetResult<Value> {
+    state
+        .request(
+            session_id,
+            "prompts/get",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+}
+
+/// Everything the session's server has written to stderr so far.
+pub async fn inspector_stderr_function(
+    state: &InspectorState,
+    session_id: &str,
+) -> MagnetResult<String> {
+    let session = state.session(session_id)?;
+    let stderr = session.lock().await.stderr();
+    Ok(stderr)
+}
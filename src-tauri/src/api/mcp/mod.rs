@@ -0,0 +1,3 @@
+pub mod client;
+pub mod core;
+pub mod view;
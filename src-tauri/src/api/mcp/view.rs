@@ -0,0 +1,101 @@
+use super::core::{
+    call_tool_function, close_inspector_session_function, get_prompt_function,
+    inspector_stderr_function, list_prompts_function, list_resources_function, list_tools_function,
+    open_inspector_session_function, read_resource_function, test_server_function,
+    InspectorSession, InspectorState, ServerTestReport, DEFAULT_TEST_TIMEOUT_SECS,
+};
+use crate::api::clients::core::McpClient;
+use crate::error::MagnetResult;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[tauri::command]
+pub async fn test_server(
+    server_id: &str,
+    client: Option<McpClient>,
+    timeout_secs: Option<u64>,
+) -> MagnetResult<ServerTestReport> {
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TEST_TIMEOUT_SECS));
+    test_server_function(server_id, client.unwrap_or_default(), timeout).await
+}
+
+#[tauri::command]
+pub async fn open_inspector_session(
+    state: tauri::State<'_, InspectorState>,
+    server_id: &str,
+    client: Option<McpClient>,
+    timeout_secs: Option<u64>,
+) -> MagnetResult<InspectorSession> {
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TEST_TIMEOUT_SECS));
+    open_inspector_session_function(&state, server_id, client.unwrap_or_default(), timeout).await
+}
+
+#[tauri::command]
+pub async fn close_inspector_session(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+) -> MagnetResult<()> {
+    close_inspector_session_function(&state, session_id).await
+}
+
+#[tauri::command]
+pub async fn list_inspector_tools(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    list_tools_function(&state, session_id).await
+}
+
+#[tauri::command]
+pub async fn call_inspector_tool(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+    name: &str,
+    arguments: Option<Value>,
+) -> MagnetResult<Value> {
+    call_tool_function(&state, session_id, name, arguments.unwrap_or(Value::Null)).await
+}
+
+#[tauri::command]
+pub async fn list_inspector_resources(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    list_resources_function(&state, session_id).await
+}
+
+#[tauri::command]
+pub async fn read_inspector_resource(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+    uri: &str,
+) -> MagnetResult<Value> {
+    read_resource_function(&state, session_id, uri).await
+}
+
+#[tauri::command]
+pub async fn list_inspector_prompts(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+) -> MagnetResult<Vec<Value>> {
+    list_prompts_function(&state, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_inspector_prompt(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+    name: &str,
+    arguments: Option<HashMap<String, String>>,
+) -> MagnetResult<Value> {
+    get_prompt_function(&state, session_id, name, arguments.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn get_inspector_stderr(
+    state: tauri::State<'_, InspectorState>,
+    session_id: &str,
+) -> MagnetResult<String> {
+    inspector_stderr_function(&state, session_id).await
+}
@@ -1,2 +1,4 @@
+pub mod clients;
 pub mod dependency;
+pub mod mcp;
 pub mod servers;
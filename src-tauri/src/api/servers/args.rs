@@ -0,0 +1,168 @@
+use super::paths::resolve_path;
+use crate::error::FieldError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Placeholder for the value in a flag such as `--root={value}`.
+const VALUE_PLACEHOLDER: &str = "{value}";
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub(crate) enum ArgClass {
+    #[default]
+    Text,
+    Select,
+    FilePath,
+    DirectoryPath,
+    /// A switch: the flag is passed alone when the value is `true`.
+    Boolean,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+enum ArgumentMultiplicity {
+    #[default]
+    Single,
+    Multiple,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InputArg {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    class: ArgClass,
+    #[serde(default)]
+    multiplicity: ArgumentMultiplicity,
+    /// Passed before each value, e.g. `--root`, or with the value substituted
+    /// when it contains `{value}`, e.g. `--port={value}`. Values are
+    /// positional when empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    flag: String,
+    #[serde(default)]
+    required: bool,
+    /// Args are placed by ascending `order`, then in the order listed.
+    #[serde(default)]
+    order: i32,
+    #[serde(default)]
+    value: Vec<String>,
+}
+
+impl InputArg {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+
+    pub(crate) fn class(&self) -> &ArgClass {
+        &self.class
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.value
+    }
+
+    pub fn set_values(&mut self, values: Vec<String>) {
+        self.value = values;
+    }
+
+    fn check(&self) -> Option<String> {
+        if self.value.is_empty() {
+            return self.required.then(|| "Required".to_string());
+        }
+        if self.multiplicity == ArgumentMultiplicity::Single && self.value.len() > 1 {
+            return Some("Takes a single value".to_string());
+        }
+        if self.class == ArgClass::Boolean
+            && self
+                .value
+                .iter()
+                .any(|value| value != "true" && value != "false")
+        {
+            return Some("Must be true or false".to_string());
+        }
+        None
+    }
+
+    /// Command line arguments for `values`, which are this arg's values or
+    /// replacements for them such as in-container paths.
+    pub fn tokens(&self, values: &[String]) -> Vec<String> {
+        if self.class == ArgClass::Boolean {
+            return match values.first().map(String::as_str) {
+                Some("true") if !self.flag.is_empty() => vec![self.flag.clone()],
+                _ => vec![],
+            };
+        }
+        let mut tokens = vec![];
+        for value in values {
+            if self.flag.contains(VALUE_PLACEHOLDER) {
+                tokens.push(self.flag.replace(VALUE_PLACEHOLDER, value));
+            } else {
+                if !self.flag.is_empty() {
+                    tokens.push(self.flag.clone());
+                }
+                tokens.push(value.clone());
+            }
+        }
+        tokens
+    }
+}
+
+/// Sets each arg's values from `values`, keyed by arg name, replacing file and
+/// directory paths with their resolved form. Returns warnings about the paths,
+/// or fails with every arg that is missing a required value, has too many or
+/// names a path that does not exist.
+pub fn assign_values(
+    mut args: Vec<InputArg>,
+    values: &HashMap<String, Vec<String>>,
+) -> Result<(Vec<InputArg>, Vec<FieldError>), Vec<FieldError>> {
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    for arg in &mut args {
+        arg.value = values
+            .get(&arg.name)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(message) = arg.check() {
+            errors.push(FieldError {
+                field: arg.name.clone(),
+                message,
+            });
+            continue;
+        }
+        let directory = match arg.class {
+            ArgClass::FilePath => false,
+            ArgClass::DirectoryPath => true,
+            _ => continue,
+        };
+        for value in &mut arg.value {
+            match resolve_path(value, directory) {
+                Ok((path, path_warnings)) => {
+                    *value = path;
+                    warnings.extend(path_warnings.into_iter().map(|message| FieldError {
+                        field: arg.name.clone(),
+                        message,
+                    }));
+                }
+                Err(message) => errors.push(FieldError {
+                    field: arg.name.clone(),
+                    message,
+                }),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok((args, warnings))
+    } else {
+        Err(errors)
+    }
+}
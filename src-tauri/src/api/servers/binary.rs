@@ -0,0 +1,158 @@
+use crate::api::dependency::core::{download_to_temp, verify_sha256};
+use crate::utils::archive;
+use crate::utils::os::get_magnet_dir;
+use anyhow::{anyhow, Result};
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A server shipped as prebuilt release binaries instead of a package.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BinarySpec {
+    version: String,
+    /// Path of the executable inside the unpacked artifact, `/`-separated and
+    /// without `.exe`. For artifacts that are not archives, the file name the
+    /// download is saved under.
+    executable: String,
+    /// Downloads keyed by `<os>-<arch>` as named by `std::env::consts`, e.g.
+    /// `macos-aarch64` or `windows-x86_64`.
+    artifacts: HashMap<String, BinaryArtifact>,
+}
+
+impl BinarySpec {
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BinaryArtifact {
+    url: String,
+    sha256: String,
+}
+
+fn platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn is_archive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".zip") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Directory holding every downloaded version of a server.
+fn server_root(server_id: &str) -> Result<PathBuf> {
+    let mut components = Path::new(server_id).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(anyhow!("Invalid server id: {}", server_id));
+    }
+    Ok(get_magnet_dir()?.join("servers").join(server_id))
+}
+
+/// The executable's path relative to the install directory. Rejects paths
+/// that would point outside of it.
+fn relative_executable(executable: &str) -> Result<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(executable).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return Err(anyhow!("Invalid executable path: {}", executable)),
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        return Err(anyhow!("Invalid executable path: {}", executable));
+    }
+    #[cfg(target_os = "windows")]
+    if relative.extension().is_none() {
+        relative.set_extension("exe");
+    }
+    Ok(relative)
+}
+
+/// Downloads and unpacks this platform's artifact into
+/// `<magnet dir>/servers/<id>/<version>`, unless that version is already
+/// there, and returns the path of the executable.
+pub async fn install_binary(server_id: &str, spec: &BinarySpec) -> Result<PathBuf> {
+    let key = platform_key();
+    let artifact = spec
+        .artifacts
+        .get(&key)
+        .ok_or_else(|| anyhow!("{} has no build for {}", server_id, key))?;
+    let relative = relative_executable(&spec.executable)?;
+    let root = server_root(server_id)?;
+    let dest = root.join(&spec.version);
+    let executable = dest.join(&relative);
+    if executable.is_file() {
+        trace!("{} is already installed", executable.to_string_lossy());
+        return Ok(executable);
+    }
+
+    let file_name = artifact.url.rsplit('/').next().unwrap_or(&artifact.url);
+    trace!("Downloading {}", artifact.url);
+    let (download, hasher) = download_to_temp(&artifact.url, file_name, |_, _| Ok(())).await?;
+    verify_sha256(file_name, hasher, &artifact.sha256.to_lowercase())?;
+
+    // Unpack next to the final location so a failed install leaves nothing
+    // that looks complete.
+    let staging = root.join(format!("{}.partial", spec.version));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    if is_archive(file_name) {
+        trace!("Extracting archive into {}", staging.to_string_lossy());
+        archive::extract(download.path(), &staging, |_| Ok(()))?;
+    } else {
+        let target = staging.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(download.path(), &target)?;
+    }
+
+    let staged = staging.join(&relative);
+    if !staged.is_file() {
+        fs::remove_dir_all(&staging)?;
+        return Err(anyhow!(
+            "{} does not contain {}",
+            file_name,
+            spec.executable
+        ));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    if dest.exists() {
+        fs::remove_dir_all(&dest)?;
+    }
+    fs::rename(&staging, &dest)?;
+    trace!("Installed {}", executable.to_string_lossy());
+    Ok(executable)
+}
+
+/// Deletes every downloaded version of the server. Failures are only logged,
+/// since a running server can keep its executable locked on Windows.
+pub fn remove_binaries(server_id: &str) {
+    let root = match server_root(server_id) {
+        Ok(root) => root,
+        Err(e) => {
+            warn!("Not removing binaries of {}: {}", server_id, e);
+            return;
+        }
+    };
+    if !root.exists() {
+        return;
+    }
+    trace!("Removing {}", root.to_string_lossy());
+    if let Err(e) = fs::remove_dir_all(&root) {
+        warn!("Failed to remove {}: {}", root.to_string_lossy(), e);
+    }
+}
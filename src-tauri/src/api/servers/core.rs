@@ -1,11 +1,34 @@
-use crate::utils::os::get_home;
-use std::borrow::Cow;
+use super::args::{assign_values, ArgClass, InputArg};
+use super::binary::{install_binary, remove_binaries, BinarySpec};
+use super::env::{validate_env, EnvVarSpec};
+use super::package::{
+    package_name, pin_package, server_versions, set_server_version, PackageIndex,
+};
+use crate::api::clients::core::{
+    set_server_metadata, target_clients, with_metadata, ClientConfig, ClientServerConfig, McpClient,
+};
+use crate::api::dependency::container::ContainerHandler;
+use crate::api::dependency::core::Dependency;
+use crate::api::dependency::resolver::{executable_name, login_shell_path, tool_dir};
+use crate::api::dependency::runtime::runtime_status;
+use crate::launcher::{launcher_args, launcher_path, LAUNCH_FLAG};
+use crate::utils::vault::Vault;
+use crate::utils::version::Version;
+use crate::error::{FieldError, MagnetError, MagnetResult};
 use crate::APP_STATE_FILENAME;
-use log::{debug};
+use futures_util::stream::{self, StreamExt};
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tauri_plugin_store::StoreExt;
-use shell_escape::escape;
+
+/// Shown to the frontend in place of secret env values. Sending it back
+/// keeps the stored value.
+pub(crate) const SECRET_MASK: &str = "********";
+
+/// How many package indexes are asked for the latest release at once.
+const UPDATE_CHECK_CONCURRENCY: usize = 8;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct BaseServer {
@@ -19,6 +42,15 @@ struct BaseServer {
     rating: u8,
     #[serde(rename = "publishDate")]
     publish_date: String,
+    /// Id of the registry source the server was loaded from.
+    #[serde(default)]
+    source: String,
+    /// Current release, when the registry tracks it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// What changed in `version`, as text or a link.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    changelog: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,200 +65,263 @@ pub struct FrontendServer {
     args: Vec<String>,
     #[serde(default)]
     guide: String,
+    /// The first of `input_args`, for frontends predating multiple args.
     #[serde(default, rename = "inputArg")]
     input_arg: InputArg,
+    #[serde(default, rename = "inputArgs")]
+    input_args: Vec<InputArg>,
+    #[serde(default, rename = "installedClients")]
+    installed_clients: Vec<McpClient>,
+    /// Version recorded when the server was installed.
+    #[serde(default, rename = "installedVersion")]
+    installed_version: Option<String>,
+    #[serde(default, rename = "secretEnv")]
+    secret_env: Vec<String>,
+    #[serde(default, rename = "envSchema")]
+    env_schema: Vec<EnvVarSpec>,
+}
+
+/// What the user should know about a finished install.
+#[derive(Debug, Serialize, Default)]
+pub struct InstallReport {
+    /// Settings that were accepted but look risky, such as paths giving access
+    /// to credentials.
+    warnings: Vec<FieldError>,
+}
+
+/// An installed server with a newer release available.
+#[derive(Debug, Serialize)]
+pub struct ServerUpdate {
+    id: String,
+    title: String,
+    #[serde(rename = "installedVersion")]
+    installed_version: String,
+    #[serde(rename = "latestVersion")]
+    latest_version: String,
+    changelog: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct SystemCommandInfo {
     command: String,
     args: Vec<String>,
+    /// The single input arg of older registries; ignored when `inputArgs` is set.
     #[serde(default, rename = "inputArg")]
     input_arg: InputArg,
+    #[serde(default, rename = "inputArgs")]
+    input_args: Vec<InputArg>,
     #[serde(default)]
     env: HashMap<String, String>,
     #[serde(default)]
     guide: String,
+    /// Env vars holding credentials. They are kept in Magnet's vault instead
+    /// of the client config and added when the server is launched.
+    #[serde(default, rename = "secretEnv")]
+    secret_env: Vec<String>,
+    /// Describes the env vars the server reads; `env` holds example values.
+    #[serde(default, rename = "envSchema")]
+    env_schema: Vec<EnvVarSpec>,
+    /// Oldest Node.js (for `npx`) or uv (for `uvx`) release the server runs on.
+    #[serde(default, rename = "minRuntimeVersion")]
+    min_runtime_version: Option<String>,
+    /// Set for servers shipped as a container image; `command` is then ignored.
+    #[serde(default)]
+    container: Option<ContainerSpec>,
+    /// Set for servers shipped as prebuilt executables; `command` is then ignored.
+    #[serde(default)]
+    binary: Option<BinarySpec>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BackendServer {
-    #[serde(flatten)]
-    base: BaseServer,
-    #[serde(rename = "commandInfo")]
-    command_info: SystemCommandInfo,
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ContainerSpec {
+    image: String,
+    /// Arguments for the image's entrypoint, placed before any input arg.
+    #[serde(default)]
+    args: Vec<String>,
+    /// Extra `run` flags, e.g. `--network=host`.
+    #[serde(default, rename = "runArgs")]
+    run_args: Vec<String>,
+    /// Where `FilePath`/`DirectoryPath` input args are mounted in the container.
+    #[serde(default = "default_mount_root", rename = "mountRoot")]
+    mount_root: String,
+    #[serde(default, rename = "readOnly")]
+    read_only: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
-enum ArgClass {
-    #[default]
-    Text,
-    Select,
-    FilePath,
-    DirectoryPath,
-}
+impl SystemCommandInfo {
+    /// Input args in command line order. Servers declaring none have none,
+    /// rather than a nameless `inputArg` that would always fail validation.
+    fn input_args(&self) -> Vec<InputArg> {
+        if self.input_args.is_empty() {
+            if self.input_arg.name().is_empty() {
+                return vec![];
+            }
+            return vec![self.input_arg.clone()];
+        }
+        let mut args = self.input_args.clone();
+        args.sort_by_key(|arg| arg.order());
+        args
+    }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
-enum ArgumentMultiplicity {
-    #[default]
-    Single,
-    Multiple,
+    /// Env vars kept in the vault: those listed in `secretEnv` and those the
+    /// schema marks secret.
+    fn secret_names(&self) -> Vec<String> {
+        let mut names = self.secret_env.clone();
+        for spec in self.env_schema.iter().filter(|spec| spec.is_secret()) {
+            if !names.iter().any(|name| name == spec.name()) {
+                names.push(spec.name().to_string());
+            }
+        }
+        names
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct InputArg {
-    #[serde(default)]
-    name: String,
-    #[serde(default)]
-    description: String,
-    #[serde(default)]
-    class: ArgClass,
-    #[serde(default)]
-    multiplicity: ArgumentMultiplicity,
-    #[serde(default)]
-    value: Vec<String>,
+fn default_mount_root() -> String {
+    "/projects".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ClientServerConfig {
-    #[serde(default)]
-    command: String,
-    args: Vec<String>,
-    #[serde(default, with = "raw_string_map")]
-    env: HashMap<String, String>,
-    #[serde(rename = "commandCreator", default)]
-    command_creator: String,
-    #[serde(rename = "inputArg", default)]
-    input_arg: InputArg,
+pub struct BackendServer {
+    #[serde(flatten)]
+    base: BaseServer,
+    #[serde(rename = "commandInfo")]
+    command_info: SystemCommandInfo,
 }
 
-mod raw_string_map {
-    use serde::{self, Deserialize, Deserializer, Serializer};
-    use std::collections::HashMap;
-
-    pub fn serialize<S>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.collect_map(map.iter().map(|(k, v)| (k, v.replace("\\n", "\n"))))
-    }
+fn get_servers_from_store<T: for<'de> Deserialize<'de>>(
+    app_handle: &tauri::AppHandle,
+) -> MagnetResult<Vec<T>> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let raw_servers_str: String = store
+        .get("servers")
+        .and_then(|v| v.as_str().map(String::from))
+        .ok_or(MagnetError::RegistryMissing)?;
+    let servers: Vec<T> = serde_json::from_str(&raw_servers_str)
+        .map_err(|e| MagnetError::RegistryParse(e.to_string()))?;
+    Ok(servers)
+}
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        HashMap::deserialize(deserializer)
-    }
+pub async fn get_client_server_config(
+    client: McpClient,
+) -> MagnetResult<HashMap<String, ClientServerConfig>> {
+    debug!("get_client_server_config core: {:?}", client);
+    let config = ClientConfig::load(client)?;
+    let id_config_map = config.into_servers();
+    debug!("get_client_server_config core: loaded id_env_map");
+    Ok(id_config_map)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ClientConfig {
-    #[serde(rename = "mcpServers", default)]
-    mcp_servers: HashMap<String, ClientServerConfig>,
-    #[serde(flatten, default)]
-    other_fields: HashMap<String, serde_json::Value>,
-}
-
-impl ClientConfig {
-    fn config_path() -> std::path::PathBuf {
-        #[cfg(target_os = "macos")]
-        {
-            get_home()
-                .unwrap()
-                .join("Library/Application Support/Claude/claude_desktop_config.json")
-        }
-        #[cfg(target_os = "windows")]
-        {
-            let appdata = std::env::var("APPDATA").unwrap();
-            std::path::PathBuf::from(appdata)
-                .join("Claude")
-                .join("claude_desktop_config.json")
+/// Server configs of every detected client, with Magnet's bookkeeping. A
+/// client whose config cannot be parsed is skipped so one broken file does not
+/// hide the others.
+pub(crate) async fn load_detected_client_configs(
+    app_handle: &tauri::AppHandle,
+) -> Vec<(McpClient, HashMap<String, ClientServerConfig>)> {
+    let mut client_configs = vec![];
+    for client in McpClient::ALL
+        .into_iter()
+        .filter(|client| client.is_detected())
+    {
+        let loaded = get_client_server_config(client)
+            .await
+            .and_then(|id_config_map| with_metadata(app_handle, client, id_config_map));
+        match loaded {
+            Ok(id_config_map) => client_configs.push((client, id_config_map)),
+            Err(e) => warn!("Skipping {}: {}", client.display_name(), e),
         }
     }
+    client_configs
+}
 
-    fn load() -> Self {
-        let config_path = Self::config_path();
-        let config = match std::fs::read_to_string(config_path) {
-            Ok(content) => content,
-            Err(_) => {
-                debug!("Config file not found, returning empty HashMap");
-                return ClientConfig {
-                    mcp_servers: HashMap::new(),
-                    other_fields: HashMap::new(),
-                };
-            }
-        };
-        debug!("ClientConfig loaded config");
-        let config: ClientConfig = serde_json::from_str(&config).unwrap();
-        debug!("ClientConfig parsed config");
+/// Input arg values an installed server was configured with, by arg name.
+fn installed_input_values(config: &ClientServerConfig) -> HashMap<String, Vec<String>> {
+    if config.input_args.is_empty() {
+        HashMap::from([(
+            config.input_arg.name().to_string(),
+            config.input_arg.values().to_vec(),
+        )])
+    } else {
         config
-    }
-
-    fn save(&self) {
-        let config_path = Self::config_path();
-        let config_str = serde_json::to_string_pretty(&self).unwrap();
-        std::fs::write(config_path.clone(), config_str).unwrap();
+            .input_args
+            .iter()
+            .map(|arg| (arg.name().to_string(), arg.values().to_vec()))
+            .collect()
     }
 }
 
-fn get_servers_from_store<T: for<'de> Deserialize<'de>>(app_handle: &tauri::AppHandle) -> Vec<T> {
-    let store = app_handle.store(APP_STATE_FILENAME).unwrap();
-    let raw_servers_str: String = serde_json::from_value(
-        store
-            .get("servers")
-            .expect("Failed to get servers from store"),
-    )
-    .unwrap();
-    let servers: Vec<T> = serde_json::from_str(&raw_servers_str).unwrap();
-    servers
-}
-
-pub async fn get_client_server_config() -> HashMap<String, ClientServerConfig> {
-    debug!("get_client_server_config core");
-    let config = ClientConfig::load();
-    let mut id_config_map = HashMap::new();
-    config
-        .mcp_servers
-        .into_iter()
-        .for_each(|(title, server_config)| {
-            id_config_map.insert(title, server_config);
-        });
-    debug!("get_client_server_config core: loaded id_env_map");
-    id_config_map
+/// Combines the values of the single `inputArg` API, which go to the server's
+/// first arg, with values given by arg name. Named values win.
+pub fn merge_input_values(
+    app_handle: &tauri::AppHandle,
+    server_id: &str,
+    input_arg: Option<Vec<String>>,
+    input_args: Option<HashMap<String, Vec<String>>>,
+) -> MagnetResult<Option<HashMap<String, Vec<String>>>> {
+    let Some(input_arg) = input_arg else {
+        return Ok(input_args);
+    };
+    let servers = get_servers_from_store::<BackendServer>(app_handle)?;
+    let server = servers
+        .iter()
+        .find(|server| server.base.id == server_id)
+        .ok_or_else(|| MagnetError::UnknownServer(server_id.to_string()))?;
+    let args = server.command_info.input_args();
+    let mut values = input_args.unwrap_or_default();
+    if let Some(first) = args.first() {
+        values.entry(first.name().to_string()).or_insert(input_arg);
+    }
+    Ok(Some(values))
 }
 
-pub async fn load_all_frontend_servers(app_handle: &tauri::AppHandle) -> Vec<FrontendServer> {
-    let backend_servers = get_servers_from_store::<BackendServer>(app_handle);
+pub async fn load_all_frontend_servers(
+    app_handle: &tauri::AppHandle,
+) -> MagnetResult<Vec<FrontendServer>> {
+    let backend_servers = get_servers_from_store::<BackendServer>(app_handle)?;
     debug!("load_all_frontend_servers core: loaded servers");
-    let id_config_map = get_client_server_config().await;
+    let client_configs = load_detected_client_configs(app_handle).await;
     debug!("load_all_frontend_servers core: loaded id_env_map");
+    let mut installed_versions = server_versions(app_handle)?;
+    let vault = Vault::open()?;
 
-    backend_servers
+    Ok(backend_servers
         .into_iter()
-        .map(|mut backend_server| {
-            let is_installed = id_config_map.contains_key(&backend_server.base.id);
-            let env = if is_installed {
-                id_config_map
-                    .get(&backend_server.base.id)
-                    .unwrap()
-                    .env
-                    .clone()
-            } else {
-                backend_server.command_info.env
-            };
-
-            let arg_values = if is_installed {
-                id_config_map
-                    .get(&backend_server.base.id)
-                    .unwrap()
-                    .input_arg
-                    .value
-                    .clone()
-            } else {
-                vec![]
+        .map(|backend_server| {
+            let installed_clients: Vec<McpClient> = client_configs
+                .iter()
+                .filter(|(_, id_config_map)| id_config_map.contains_key(&backend_server.base.id))
+                .map(|(client, _)| *client)
+                .collect();
+            let installed_config = client_configs
+                .iter()
+                .find_map(|(_, id_config_map)| id_config_map.get(&backend_server.base.id));
+            let is_installed = installed_config.is_some();
+            let secret_env = backend_server.command_info.secret_names();
+            let mut input_args = backend_server.command_info.input_args();
+            let mut env = match installed_config {
+                Some(server_config) => server_config.env.clone(),
+                None => backend_server.command_info.env,
             };
+            if is_installed && !secret_env.is_empty() {
+                let stored = vault.get(&backend_server.base.id).unwrap_or_else(|e| {
+                    warn!("Failed to read secrets of {}: {}", backend_server.base.id, e);
+                    HashMap::new()
+                });
+                for name in &secret_env {
+                    if env.contains_key(name) || stored.contains_key(name) {
+                        env.insert(name.clone(), SECRET_MASK.to_string());
+                    }
+                }
+            }
 
-            backend_server.command_info.input_arg.value = arg_values;
+            let installed_values = installed_config
+                .map(installed_input_values)
+                .unwrap_or_default();
+            for arg in &mut input_args {
+                arg.set_values(installed_values.get(arg.name()).cloned().unwrap_or_default());
+            }
+            let installed_version = installed_versions
+                .remove(&backend_server.base.id)
+                .filter(|_| is_installed);
 
             FrontendServer {
                 base: backend_server.base,
@@ -234,139 +329,864 @@ pub async fn load_all_frontend_servers(app_handle: &tauri::AppHandle) -> Vec<Fro
                 env,
                 guide: backend_server.command_info.guide,
                 args: backend_server.command_info.args,
-                input_arg: backend_server.command_info.input_arg,
+                input_arg: input_args.first().cloned().unwrap_or_default(),
+                input_args,
+                installed_clients,
+                installed_version,
+                secret_env,
+                env_schema: backend_server.command_info.env_schema,
             }
         })
-        .collect()
+        .collect())
 }
 
 pub async fn load_all_installed_frontend_servers(
     app_handle: &tauri::AppHandle,
-) -> Vec<FrontendServer> {
-    let servers = load_all_frontend_servers(app_handle).await;
-    servers
+) -> MagnetResult<Vec<FrontendServer>> {
+    let servers = load_all_frontend_servers(app_handle).await?;
+    Ok(servers
         .into_iter()
         .filter(|server| server.is_installed)
-        .collect()
+        .collect())
+}
+
+/// Fails with `RuntimeOutdated` when the runtime the server needs is older
+/// than `required`. Unparseable requirements are ignored.
+async fn check_runtime_version(
+    app_handle: &tauri::AppHandle,
+    dependency: Dependency,
+    required: Option<&str>,
+) -> MagnetResult<()> {
+    let Some(required) = required.and_then(Version::parse) else {
+        return Ok(());
+    };
+    let status = runtime_status(app_handle, dependency).await?;
+    match status.version() {
+        Some(found) if found >= required => Ok(()),
+        found => Err(MagnetError::RuntimeOutdated {
+            runtime: dependency.display_name().to_string(),
+            required: required.to_string(),
+            found: found.map_or_else(|| "none".to_string(), |v| v.to_string()),
+        }),
+    }
+}
+
+/// One `key=value` field of a `--mount` option. Docker reads the option as a
+/// CSV record, so a value holding a comma or a quote is quoted.
+fn mount_field(key: &str, value: &str) -> String {
+    let field = format!("{}={}", key, value);
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Builds the `run` arguments for a container server. Env values stay in the
+/// client's `env` map and are only passed through by name with `-e`, so they
+/// never appear on the command line. File and directory input args are bind
+/// mounted under `mount_root` and the server gets the in-container paths.
+fn container_args(
+    spec: &ContainerSpec,
+    env: &HashMap<String, String>,
+    input_args: &[InputArg],
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["run".into(), "-i".into(), "--rm".into()];
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort();
+    for key in keys {
+        args.push("-e".to_string());
+        args.push(key.clone());
+    }
+
+    let mut entrypoint_args = spec.args.clone();
+    let mount_root = spec.mount_root.trim_end_matches('/');
+    let mut targets: Vec<String> = vec![];
+    for input_arg in input_args {
+        match input_arg.class() {
+            ArgClass::FilePath | ArgClass::DirectoryPath => {
+                let mut arg_targets: Vec<String> = vec![];
+                for host in input_arg.values() {
+                    let i = targets.len();
+                    let name = Path::new(host)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| format!("input-{}", i));
+                    let mut target = format!("{}/{}", mount_root, name);
+                    let mut suffix = i;
+                    while targets.contains(&target) {
+                        target = format!("{}/{}-{}", mount_root, name, suffix);
+                        suffix += 1;
+                    }
+                    let mut mount = format!(
+                        "type=bind,{},{}",
+                        mount_field("src", host),
+                        mount_field("dst", &target)
+                    );
+                    if spec.read_only {
+                        mount.push_str(",readonly");
+                    }
+                    args.push("--mount".to_string());
+                    args.push(mount);
+                    targets.push(target.clone());
+                    arg_targets.push(target);
+                }
+                entrypoint_args.extend(input_arg.tokens(&arg_targets));
+            }
+            _ => entrypoint_args.extend(input_arg.tokens(input_arg.values())),
+        }
+    }
+
+    args.extend(spec.run_args.iter().cloned());
+    args.push(spec.image.clone());
+    args.extend(entrypoint_args);
+    args
+}
+
+/// The executable and leading arguments that run `launcher` from the runtime
+/// in `runtime_dir` without going through a shell.
+fn launcher_command(launcher: &str, runtime_dir: &Path) -> (PathBuf, Vec<String>) {
+    let executable = runtime_dir.join(executable_name(launcher));
+    if executable.is_file() {
+        // `npx.cmd` can only be started through `cmd.exe`, so run the script
+        // behind it with Node.js directly.
+        #[cfg(target_os = "windows")]
+        if launcher == "npx" {
+            let script = runtime_dir
+                .join("node_modules")
+                .join("npm")
+                .join("bin")
+                .join("npx-cli.js");
+            if script.is_file() {
+                return (
+                    runtime_dir.join(executable_name("node")),
+                    vec![script.to_string_lossy().to_string()],
+                );
+            }
+        }
+        return (executable, vec![]);
+    }
+    match launcher {
+        // Managed Bun is a single binary without the `bunx` alias.
+        "bunx" => (runtime_dir.join(executable_name("bun")), vec!["x".to_string()]),
+        _ => (executable, vec![]),
+    }
+}
+
+/// `path` with `dir` first, so the scripts a launcher starts find the same
+/// runtime.
+fn path_with(dir: &Path, path: &str) -> Option<String> {
+    let mut paths = vec![dir.to_path_buf()];
+    paths.extend(
+        std::env::split_paths(path).filter(|path| path != dir && !path.as_os_str().is_empty()),
+    );
+    match std::env::join_paths(paths) {
+        Ok(path) => Some(path.to_string_lossy().to_string()),
+        Err(e) => {
+            warn!("Not setting PATH for {}: {}", dir.to_string_lossy(), e);
+            None
+        }
+    }
+}
+
+/// Puts `runtime_dir` first on the server's `PATH`, so the scripts a launcher
+/// starts find the same runtime. Extends the `PATH` the user set, else
+/// `login_path`: Magnet's own is the minimal one of a GUI app.
+fn set_runtime_path(
+    env: &mut HashMap<String, String>,
+    runtime_dir: &Path,
+    login_path: Option<&str>,
+) {
+    let base = env
+        .get("PATH")
+        .map(String::as_str)
+        .or(login_path)
+        .unwrap_or_default();
+    if let Some(path) = path_with(runtime_dir, base) {
+        env.insert("PATH".to_string(), path);
+    }
 }
 
 pub async fn install_server_function(
     app_handle: &tauri::AppHandle,
     server_id: &str,
     env: Option<HashMap<String, String>>,
-    input_arg: Option<Vec<String>>,
-) -> bool {
-    let mut servers = get_servers_from_store::<BackendServer>(app_handle);
+    input_args: Option<HashMap<String, Vec<String>>>,
+    clients: &[McpClient],
+) -> MagnetResult<InstallReport> {
+    let mut servers = get_servers_from_store::<BackendServer>(app_handle)?;
     let server = servers
         .iter_mut()
         .find(|server| server.base.id == server_id)
-        .unwrap();
+        .ok_or_else(|| MagnetError::UnknownServer(server_id.to_string()))?;
+    let secret_names = server.command_info.secret_names();
+    let (mut env, stored_secrets) = prepare_env(server_id, &server.command_info, env)?;
+    let (input_args, warnings) = assign_values(
+        server.command_info.input_args(),
+        &input_args.unwrap_or_default(),
+    )
+    .map_err(MagnetError::Validation)?;
+    let input_tokens: Vec<String> = input_args
+        .iter()
+        .flat_map(|arg| arg.tokens(arg.values()))
+        .collect();
+    // Keep the version the server already runs so changing its settings or
+    // adding a client does not upgrade it; `upgrade_server` forgets it first.
+    let pinned = server_versions(app_handle)?.get(server_id).cloned();
+    let version = if let Some(binary) = &server.command_info.binary {
+        Some(binary.version().to_string())
+    } else if let Some(index) = PackageIndex::for_launcher(&server.command_info.command) {
+        match pin_package(index, &mut server.command_info.args, pinned.as_deref()).await {
+            Ok(version) => version,
+            Err(e) => {
+                warn!("Could not resolve the version of {}: {}", server_id, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
     let mut command = server.command_info.command.clone();
-    let mut arg_configs = server.command_info.args.join(" ");
-    let mut input_arg_config = server.command_info.input_arg.clone();
-    let env = env.unwrap_or_else(|| server.command_info.env.clone());
-    if input_arg.is_some() {
-        input_arg_config.value = input_arg.unwrap();
-        arg_configs = format!("{} {}", arg_configs, escape(Cow::from(input_arg_config.value.join(" "))));
-    }
+    let mut args = server.command_info.args.clone();
+    args.extend(input_tokens);
 
-    let mut config = ClientConfig::load();
-    let store = app_handle.store(APP_STATE_FILENAME).unwrap();
-    let mut args = vec![];
+    let store = app_handle.store(APP_STATE_FILENAME)?;
 
-    if command == "npx" {
-        let use_system_node = store
-            .get("use_system_node")
+    if let Some(container) = &server.command_info.container {
+        let engine = ContainerHandler::detect(app_handle)
+            .await?
+            .ok_or_else(|| MagnetError::DependencyMissing("Docker or Podman".to_string()))?;
+        command = engine.path().to_string();
+        args = container_args(container, &env, &input_args);
+    } else if let Some(binary) = &server.command_info.binary {
+        let executable = install_binary(server_id, binary).await?;
+        command = executable.to_string_lossy().to_string();
+    } else if let Some(dependency) = Dependency::for_launcher(&command) {
+        let use_system = store
+            .get(dependency.use_system_key())
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
-        let node_path = store
-            .get("node_path")
+        let runtime_path = store
+            .get(dependency.path_key())
             .and_then(|s| s.as_str().map(String::from))
             .unwrap_or("".to_owned());
-        if !use_system_node {
-            #[cfg(target_os = "macos")]
-            {
-                command = "sh".to_string();
-                args = vec![
-                    "-c".to_string(),
-                    format!("PATH=\"{}:$PATH\" npx {}", node_path, arg_configs),
-                ];
+        if !use_system && runtime_path.is_empty() {
+            return Err(MagnetError::DependencyMissing(
+                dependency.display_name().to_string(),
+            ));
+        }
+        check_runtime_version(
+            app_handle,
+            dependency,
+            server.command_info.min_runtime_version.as_deref(),
+        )
+        .await?;
+        let runtime_dir = Some(runtime_path)
+            .filter(|path| !path.is_empty())
+            .map(|path| tool_dir(&path))
+            .filter(|dir| dir.is_dir());
+        if let Some(runtime_dir) = runtime_dir {
+            let (executable, mut launcher_args) = launcher_command(&command, &runtime_dir);
+            command = executable.to_string_lossy().to_string();
+            launcher_args.extend(args);
+            args = launcher_args;
+            let login_path = match env.contains_key("PATH") {
+                true => None,
+                false => login_shell_path().await,
+            };
+            set_runtime_path(&mut env, &runtime_dir, login_path.as_deref());
+        }
+    }
+
+    if store_secrets(&Vault::open()?, server_id, &secret_names, stored_secrets, &mut env)? {
+        let launcher = launcher_path()?.to_string_lossy().to_string();
+        args = launcher_args(server_id, command, args);
+        command = launcher;
+    }
+
+    let server_config = ClientServerConfig {
+        command,
+        args,
+        env,
+        command_creator: "Magnet".to_string(),
+        input_arg: input_args.first().cloned().unwrap_or_default(),
+        input_args,
+    };
+    for client in clients {
+        let mut config = ClientConfig::load(*client)?;
+        config.insert(server_id, server_config.clone())?;
+        config.save()?;
+        set_server_metadata(app_handle, *client, server_id, Some(server_config.metadata()))?;
+    }
+    set_server_version(app_handle, server_id, version.as_deref())?;
+    Ok(InstallReport { warnings })
+}
+
+/// The env to install the server with, defaulting to the registry's, with
+/// schema defaults filled in, and the secrets already in the vault. Fails
+/// with `Validation` when the env does not satisfy the server's schema.
+fn prepare_env(
+    server_id: &str,
+    command_info: &SystemCommandInfo,
+    env: Option<HashMap<String, String>>,
+) -> MagnetResult<(HashMap<String, String>, HashMap<String, String>)> {
+    let mut env = env.unwrap_or_else(|| command_info.env.clone());
+    let stored_secrets = if command_info.secret_names().is_empty() {
+        HashMap::new()
+    } else {
+        Vault::open()?.get(server_id)?
+    };
+    validate_env(&command_info.env_schema, &mut env, &stored_secrets)
+        .map_err(MagnetError::Validation)?;
+    Ok((env, stored_secrets))
+}
+
+/// Moves the server's secret env vars out of `env` and into the vault. A
+/// masked or missing value keeps the stored secret and an empty one removes
+/// it. Returns whether the server has secrets to be launched with.
+fn store_secrets(
+    vault: &Vault,
+    server_id: &str,
+    secret_env: &[String],
+    mut secrets: HashMap<String, String>,
+    env: &mut HashMap<String, String>,
+) -> anyhow::Result<bool> {
+    if secret_env.is_empty() {
+        return Ok(false);
+    }
+    for name in secret_env {
+        match env.remove(name) {
+            Some(value) if value == SECRET_MASK => {}
+            Some(value) if value.is_empty() => {
+                secrets.remove(name);
             }
-            #[cfg(target_os = "windows")]
-            {
-                command = "cmd".to_string();
-                args = vec![
-                    "/c".to_string(),
-                    format!("set PATH=%PATH%;{} && npx {}", node_path, arg_configs),
-                ];
+            Some(value) => {
+                secrets.insert(name.clone(), value);
             }
+            None => {}
         }
-    } else if command == "uvx" {
-        let use_system_uv = store
-            .get("use_system_uv")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-        let uv_path = store
-            .get("uv_path")
-            .and_then(|s| s.as_str().map(String::from))
-            .unwrap_or("".to_owned());
-        if !use_system_uv {
-            #[cfg(target_os = "macos")]
-            {
-                command = "sh".to_string();
-                args = vec![
-                    "-c".to_string(),
-                    format!("PATH=\"{}:$PATH\" uvx {}", uv_path, arg_configs),
-                ];
+    }
+    secrets.retain(|name, _| secret_env.contains(name));
+    let has_secrets = !secrets.is_empty();
+    vault.set(server_id, secrets)?;
+    Ok(has_secrets)
+}
+
+/// Points the servers started through Magnet at its current executable,
+/// which moves when Magnet is updated or its AppImage is moved.
+pub fn repoint_launchers() -> MagnetResult<()> {
+    let launcher = launcher_path()?.to_string_lossy().to_string();
+    for client in McpClient::ALL
+        .into_iter()
+        .filter(|client| client.is_detected())
+    {
+        let repointed = (|| -> MagnetResult<usize> {
+            let mut config = ClientConfig::load(client)?;
+            let stale: Vec<String> = config
+                .servers()
+                .iter()
+                .filter(|(_, server_config)| {
+                    server_config.args.first().map(String::as_str) == Some(LAUNCH_FLAG)
+                        && server_config.command != launcher
+                })
+                .map(|(server_id, _)| server_id.clone())
+                .collect();
+            for server_id in &stale {
+                config.set_command(server_id, &launcher)?;
             }
-            #[cfg(target_os = "windows")]
-            {
-                command = "cmd".to_string();
-                args = vec![
-                    "/c".to_string(),
-                    format!("set PATH=%PATH%;{} && uvx {}", uv_path, arg_configs),
-                ];
+            if !stale.is_empty() {
+                config.save()?;
             }
+            Ok(stale.len())
+        })();
+        match repointed {
+            Ok(0) => {}
+            Ok(count) => debug!(
+                "Pointed {} {} servers at {}",
+                count,
+                client.display_name(),
+                launcher
+            ),
+            Err(e) => warn!("Skipping {}: {}", client.display_name(), e),
         }
     }
+    Ok(())
+}
 
-    if args.is_empty() {
-        args = server.command_info.args.clone();
+/// Removes the server from `clients`, or from every detected client that has
+/// it when `clients` is `None`.
+pub async fn uninstall_server_function(
+    app_handle: &tauri::AppHandle,
+    server_id: &str,
+    clients: Option<&[McpClient]>,
+) -> MagnetResult<()> {
+    let clients = match clients {
+        Some(clients) => clients.to_vec(),
+        None => installed_clients(app_handle, server_id).await,
+    };
+    for client in clients {
+        let mut config = ClientConfig::load(client)?;
+        if config.remove(server_id)?.is_some() {
+            config.save()?;
+        }
+        set_server_metadata(app_handle, client, server_id, None)?;
     }
-
-    config.mcp_servers.insert(
-        server_id.to_string(),
-        ClientServerConfig {
-            command,
-            args,
-            env,
-            command_creator: "Magnet".to_string(),
-            input_arg: input_arg_config,
-        },
-    );
-    config.save();
-    true
+    Ok(())
 }
 
-pub async fn uninstall_server_function(server_id: &str) -> bool {
-    let mut config = ClientConfig::load();
-    config.mcp_servers.remove(&server_id.to_string());
-    config.save();
-    true
+/// Deletes the server's downloaded binaries, secrets and recorded version once
+/// no detected client has it installed anymore.
+pub async fn forget_unused_server(
+    app_handle: &tauri::AppHandle,
+    server_id: &str,
+) -> MagnetResult<()> {
+    if installed_clients(app_handle, server_id).await.is_empty() {
+        remove_binaries(server_id);
+        Vault::open()?.set(server_id, HashMap::new())?;
+        set_server_version(app_handle, server_id, None)?;
+    }
+    Ok(())
 }
 
+/// Reinstalls the server with new settings into `clients`, defaulting to the
+/// clients it is currently installed in, or the install targets if none.
 pub async fn update_server_function(
     app_handle: &tauri::AppHandle,
     server_id: &str,
     env: Option<HashMap<String, String>>,
-    input_arg: Option<Vec<String>>,
-) -> bool {
-    let mut config = ClientConfig::load();
-    if !config.mcp_servers.contains_key(server_id) {
-        install_server_function(&app_handle, server_id, env, input_arg).await;
-    } else {
-        uninstall_server_function(server_id).await;
-        install_server_function(&app_handle, server_id, env, input_arg).await;
+    input_args: Option<HashMap<String, Vec<String>>>,
+    clients: Option<&[McpClient]>,
+) -> MagnetResult<InstallReport> {
+    let clients = match clients {
+        Some(clients) => clients.to_vec(),
+        None => {
+            let installed = installed_clients(app_handle, server_id).await;
+            if installed.is_empty() {
+                target_clients(app_handle)?
+            } else {
+                installed
+            }
+        }
+    };
+    // Check the new settings before the working install is removed.
+    let servers = get_servers_from_store::<BackendServer>(app_handle)?;
+    let server = servers
+        .iter()
+        .find(|server| server.base.id == server_id)
+        .ok_or_else(|| MagnetError::UnknownServer(server_id.to_string()))?;
+    prepare_env(server_id, &server.command_info, env.clone())?;
+    assign_values(
+        server.command_info.input_args(),
+        input_args.as_ref().unwrap_or(&HashMap::new()),
+    )
+    .map_err(MagnetError::Validation)?;
+
+    uninstall_server_function(app_handle, server_id, Some(&clients)).await?;
+    install_server_function(app_handle, server_id, env, input_args, &clients).await
+}
+
+/// Reinstalls the server at its latest release into every client it is
+/// installed in, keeping its env and input args. Returns the new version.
+pub async fn upgrade_server_function(
+    app_handle: &tauri::AppHandle,
+    server_id: &str,
+) -> MagnetResult<Option<String>> {
+    let installed: Vec<(McpClient, ClientServerConfig)> = load_detected_client_configs(app_handle)
+        .await
+        .into_iter()
+        .filter_map(|(client, mut id_config_map)| {
+            id_config_map.remove(server_id).map(|config| (client, config))
+        })
+        .collect();
+    let Some((_, config)) = installed.first() else {
+        return Err(MagnetError::ServerNotInstalled(server_id.to_string()));
+    };
+    let env = config.env.clone();
+    let input_args = Some(installed_input_values(config));
+    let clients: Vec<McpClient> = installed.iter().map(|(client, _)| *client).collect();
+
+    // Without a recorded version the install pins the latest release.
+    let previous = server_versions(app_handle)?.remove(server_id);
+    set_server_version(app_handle, server_id, None)?;
+    if let Err(e) =
+        update_server_function(app_handle, server_id, Some(env), input_args, Some(&clients)).await
+    {
+        set_server_version(app_handle, server_id, previous.as_deref())?;
+        return Err(e);
+    }
+    Ok(server_versions(app_handle)?.remove(server_id))
+}
+
+/// Latest release of the server: the registry's `version` if it has one,
+/// otherwise the binary's version or the package index's latest release.
+async fn latest_server_version(server: &BackendServer) -> Option<String> {
+    if let Some(version) = &server.base.version {
+        return Some(version.clone());
+    }
+    if let Some(binary) = &server.command_info.binary {
+        return Some(binary.version().to_string());
+    }
+    let index = PackageIndex::for_launcher(&server.command_info.command)?;
+    let name = package_name(index, &server.command_info.args)?;
+    match index.latest_version(&name).await {
+        Ok(version) => Some(version),
+        Err(e) => {
+            warn!("Could not check {} for updates: {}", server.base.id, e);
+            None
+        }
+    }
+}
+
+/// Whether `latest` is a newer release than `installed`. Versions that do not
+/// parse are never reported as updates.
+fn is_outdated(installed: &str, latest: &str) -> bool {
+    match (Version::parse(installed), Version::parse(latest)) {
+        (Some(installed), Some(latest)) => latest > installed,
+        _ => false,
+    }
+}
+
+/// Installed servers whose recorded version is older than the latest release.
+/// Servers installed without a recorded version, or whose versions cannot be
+/// compared, are skipped.
+pub async fn check_server_updates_function(
+    app_handle: &tauri::AppHandle,
+) -> MagnetResult<Vec<ServerUpdate>> {
+    let servers = get_servers_from_store::<BackendServer>(app_handle)?;
+    let installed_versions = server_versions(app_handle)?;
+    let client_configs = load_detected_client_configs(app_handle).await;
+
+    let installed: Vec<(BackendServer, String)> = servers
+        .into_iter()
+        .filter(|server| {
+            client_configs
+                .iter()
+                .any(|(_, id_config_map)| id_config_map.contains_key(&server.base.id))
+        })
+        .filter_map(|server| {
+            let installed = installed_versions.get(&server.base.id)?.clone();
+            Some((server, installed))
+        })
+        .collect();
+    let checked: Vec<(BackendServer, String, Option<String>)> = stream::iter(installed)
+        .map(|(server, installed)| async move {
+            let latest = latest_server_version(&server).await;
+            (server, installed, latest)
+        })
+        .buffered(UPDATE_CHECK_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut updates = vec![];
+    for (server, installed, latest) in checked {
+        let Some(latest) = latest else {
+            continue;
+        };
+        if is_outdated(&installed, &latest) {
+            updates.push(ServerUpdate {
+                id: server.base.id,
+                title: server.base.title,
+                installed_version: installed,
+                latest_version: latest,
+                changelog: server.base.changelog,
+            });
+        }
+    }
+    Ok(updates)
+}
+
+async fn installed_clients(app_handle: &tauri::AppHandle, server_id: &str) -> Vec<McpClient> {
+    load_detected_client_configs(app_handle)
+        .await
+        .into_iter()
+        .filter(|(_, id_config_map)| id_config_map.contains_key(server_id))
+        .map(|(client, _)| client)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn path_arg(values: &[&str]) -> InputArg {
+        serde_json::from_value(json!({
+            "name": "paths",
+            "class": "DirectoryPath",
+            "multiplicity": "Multiple",
+            "value": values,
+        }))
+        .unwrap()
+    }
+
+    fn container() -> ContainerSpec {
+        serde_json::from_value(json!({ "image": "example/server", "mountRoot": "/data" })).unwrap()
+    }
+
+    fn mounts(args: &[String]) -> Vec<&str> {
+        args.windows(2)
+            .filter(|pair| pair[0] == "--mount")
+            .map(|pair| pair[1].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn quotes_mount_fields_with_commas() {
+        let args = container_args(
+            &container(),
+            &HashMap::new(),
+            &[path_arg(&["/home/me/a,b=c", "/home/me/say \"hi\""])],
+        );
+        assert_eq!(
+            mounts(&args),
+            [
+                r#"type=bind,"src=/home/me/a,b=c","dst=/data/a,b=c""#,
+                r#"type=bind,"src=/home/me/say ""hi""","dst=/data/say ""hi""""#,
+            ]
+        );
+        assert_eq!(args[args.len() - 2..], ["/data/a,b=c", "/data/say \"hi\""]);
+    }
+
+    #[test]
+    fn mount_targets_stay_unique() {
+        let args = container_args(
+            &container(),
+            &HashMap::new(),
+            &[path_arg(&["/a/x-2", "/b/x", "/c/x"])],
+        );
+        assert_eq!(
+            mounts(&args),
+            [
+                "type=bind,src=/a/x-2,dst=/data/x-2",
+                "type=bind,src=/b/x,dst=/data/x",
+                "type=bind,src=/c/x,dst=/data/x-3",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_only_newer_releases() {
+        assert!(is_outdated("1.2.3", "1.10.0"));
+        assert!(is_outdated("v0.9.0", "1.0.0"));
+        assert!(!is_outdated("1.2.3", "1.2.3"));
+        assert!(!is_outdated("2.0.0", "1.9.9"));
+        assert!(!is_outdated("1.2.3", "latest"));
+        assert!(!is_outdated("main", "1.2.3"));
+    }
+
+    #[test]
+    fn keeps_masked_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::with_key_file(dir.path());
+        let secret_names = vec!["TOKEN".to_string(), "KEY".to_string()];
+        let stored = HashMap::from([
+            ("TOKEN".to_string(), "old-token".to_string()),
+            ("KEY".to_string(), "old-key".to_string()),
+            ("DROPPED".to_string(), "no longer a secret".to_string()),
+        ]);
+        let mut env = HashMap::from([
+            ("TOKEN".to_string(), SECRET_MASK.to_string()),
+            ("KEY".to_string(), "new-key".to_string()),
+            ("DEBUG".to_string(), "1".to_string()),
+        ]);
+
+        assert!(store_secrets(&vault, "server", &secret_names, stored, &mut env).unwrap());
+        assert_eq!(env, HashMap::from([("DEBUG".to_string(), "1".to_string())]));
+        assert_eq!(
+            vault.get("server").unwrap(),
+            HashMap::from([
+                ("TOKEN".to_string(), "old-token".to_string()),
+                ("KEY".to_string(), "new-key".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_values_remove_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::with_key_file(dir.path());
+        let secret_names = vec!["TOKEN".to_string()];
+        let stored = HashMap::from([("TOKEN".to_string(), "old-token".to_string())]);
+        vault.set("server", stored.clone()).unwrap();
+        let mut env = HashMap::from([("TOKEN".to_string(), String::new())]);
+
+        assert!(!store_secrets(&vault, "server", &secret_names, stored, &mut env).unwrap());
+        assert!(env.is_empty());
+        assert!(vault.get("server").unwrap().is_empty());
+    }
+
+    #[test]
+    fn input_args_only_lists_declared_args() {
+        let command_info = |value: serde_json::Value| -> SystemCommandInfo {
+            serde_json::from_value(value).unwrap()
+        };
+        let names = |command_info: SystemCommandInfo| -> Vec<String> {
+            command_info
+                .input_args()
+                .iter()
+                .map(|arg| arg.name().to_string())
+                .collect()
+        };
+
+        assert!(names(command_info(json!({ "command": "npx", "args": [] }))).is_empty());
+        assert_eq!(
+            names(command_info(json!({
+                "command": "npx",
+                "args": [],
+                "inputArg": { "name": "root" },
+            }))),
+            ["root"]
+        );
+        assert_eq!(
+            names(command_info(json!({
+                "command": "npx",
+                "args": [],
+                "inputArg": { "name": "ignored" },
+                "inputArgs": [
+                    { "name": "second", "order": 2 },
+                    { "name": "first", "order": 1 },
+                ],
+            }))),
+            ["first", "second"]
+        );
+    }
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn launcher_command_runs_managed_bun_for_bunx() {
+        let dir = tempfile::tempdir().unwrap();
+        let bun = dir.path().join(executable_name("bun"));
+        touch(&bun);
+        assert_eq!(launcher_command("bunx", dir.path()), (bun, vec!["x".to_string()]));
+    }
+
+    #[test]
+    fn launcher_command_prefers_the_launcher_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        let bunx = dir.path().join(executable_name("bunx"));
+        touch(&bunx);
+        touch(&dir.path().join(executable_name("bun")));
+        assert_eq!(launcher_command("bunx", dir.path()), (bunx, vec![]));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn launcher_command_runs_npx_through_node() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(&dir.path().join("npx.cmd"));
+        touch(&dir.path().join("node.exe"));
+        let script = dir
+            .path()
+            .join("node_modules")
+            .join("npm")
+            .join("bin")
+            .join("npx-cli.js");
+        touch(&script);
+        assert_eq!(
+            launcher_command("npx", dir.path()),
+            (
+                dir.path().join("node.exe"),
+                vec![script.to_string_lossy().to_string()]
+            )
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn launcher_command_runs_npx_directly() {
+        let dir = tempfile::tempdir().unwrap();
+        let npx = dir.path().join("npx");
+        touch(&npx);
+        assert_eq!(launcher_command("npx", dir.path()), (npx, vec![]));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn path_with_prepends_runtime_dir() {
+        let dir = Path::new("/home/me/My Runtimes/node/bin");
+        assert_eq!(
+            path_with(dir, "/usr/bin:/home/me/My Runtimes/node/bin:/bin").as_deref(),
+            Some("/home/me/My Runtimes/node/bin:/usr/bin:/bin")
+        );
+        assert_eq!(
+            path_with(dir, "").as_deref(),
+            Some("/home/me/My Runtimes/node/bin")
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn path_with_prepends_runtime_dir() {
+        let dir = Path::new(r"C:\Users\Me\AppData\Local\node\node v22");
+        assert_eq!(
+            path_with(
+                dir,
+                r"C:\Windows\system32;C:\Users\Me\AppData\Local\node\node v22;C:\Program Files\Git\cmd"
+            )
+            .as_deref(),
+            Some(
+                r"C:\Users\Me\AppData\Local\node\node v22;C:\Windows\system32;C:\Program Files\Git\cmd"
+            )
+        );
+    }
+
+    #[test]
+    fn runtime_dir_leads_path_without_user_path() {
+        let dir = std::env::temp_dir().join("magnet-runtime");
+        let login_path = std::env::join_paths(["/usr/local/bin", "/usr/bin"]).unwrap();
+        let mut env = HashMap::new();
+        set_runtime_path(&mut env, &dir, login_path.to_str());
+        let path = env.get("PATH").expect("PATH is set");
+        let paths: Vec<PathBuf> = std::env::split_paths(path).collect();
+        assert_eq!(paths[0], dir);
+        assert_eq!(paths[1..], [PathBuf::from("/usr/local/bin"), PathBuf::from("/usr/bin")]);
+
+        let mut env = HashMap::new();
+        set_runtime_path(&mut env, &dir, None);
+        assert_eq!(env.get("PATH"), Some(&dir.to_string_lossy().to_string()));
+    }
+
+    #[test]
+    fn runtime_dir_extends_user_path() {
+        let dir = std::env::temp_dir().join("magnet-runtime");
+        let user_path = std::env::join_paths(["/opt/tools"]).unwrap();
+        let mut env = HashMap::from([(
+            "PATH".to_string(),
+            user_path.to_string_lossy().to_string(),
+        )]);
+        set_runtime_path(&mut env, &dir, Some("/usr/bin"));
+        let paths: Vec<PathBuf> = std::env::split_paths(&env["PATH"]).collect();
+        assert_eq!(paths, [dir, PathBuf::from("/opt/tools")]);
+    }
+
+    #[test]
+    fn input_values_stay_single_arguments() {
+        let arg: InputArg = serde_json::from_value(json!({
+            "name": "query",
+            "multiplicity": "Multiple",
+            "flag": "--query",
+            "value": ["two words", "say \"hi\"", "--not-a-flag"],
+        }))
+        .unwrap();
+        let tokens = arg.tokens(arg.values());
+        assert_eq!(
+            tokens,
+            [
+                "--query",
+                "two words",
+                "--query",
+                "say \"hi\"",
+                "--query",
+                "--not-a-flag"
+            ]
+        );
+
+        let args = launcher_args("server", "/opt/node/bin/npx".to_string(), tokens.clone());
+        assert_eq!(args[..4], [LAUNCH_FLAG, "server", "--", "/opt/node/bin/npx"]);
+        assert_eq!(args[4..], tokens[..]);
     }
-    true
 }
@@ -0,0 +1,97 @@
+use super::core::SECRET_MASK;
+use crate::error::FieldError;
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Describes one env var a server reads, so the UI can render a proper field
+/// and installs can be checked before a broken config is written.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnvVarSpec {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    required: bool,
+    /// Kept in the vault like the names in `secretEnv`.
+    #[serde(default)]
+    secret: bool,
+    /// Regular expression the whole value must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    /// The only values accepted, when set.
+    #[serde(default, rename = "enum", skip_serializing_if = "Option::is_none")]
+    allowed: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    #[serde(default, rename = "docsUrl", skip_serializing_if = "Option::is_none")]
+    docs_url: Option<String>,
+}
+
+impl EnvVarSpec {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    fn check(&self, value: &str) -> Option<String> {
+        if let Some(allowed) = &self.allowed {
+            if !allowed.iter().any(|allowed| allowed == value) {
+                return Some(format!("Must be one of {}", allowed.join(", ")));
+            }
+        }
+        let pattern = self.pattern.as_ref()?;
+        match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) if regex.is_match(value) => None,
+            Ok(_) => Some(format!("Does not match the expected format {}", pattern)),
+            Err(e) => {
+                warn!("Ignoring invalid pattern of {}: {}", self.name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Fills in schema defaults for missing vars and checks every described var.
+/// Secrets sent back masked, or left out, count as set when `stored_secrets`
+/// has them. Vars without a spec are passed through unchecked.
+pub fn validate_env(
+    schema: &[EnvVarSpec],
+    env: &mut HashMap<String, String>,
+    stored_secrets: &HashMap<String, String>,
+) -> Result<(), Vec<FieldError>> {
+    let mut errors = vec![];
+    for spec in schema {
+        if !env.contains_key(&spec.name) {
+            if let Some(default) = &spec.default {
+                env.insert(spec.name.clone(), default.clone());
+            }
+        }
+        let stored = stored_secrets.contains_key(&spec.name);
+        let value = match env.get(&spec.name).map(String::as_str) {
+            Some(SECRET_MASK) | None if stored => continue,
+            Some(SECRET_MASK) | Some("") | None => None,
+            Some(value) => Some(value),
+        };
+        let message = match value {
+            None if spec.required => Some("Required".to_string()),
+            None => None,
+            Some(value) => spec.check(value),
+        };
+        if let Some(message) = message {
+            errors.push(FieldError {
+                field: spec.name.clone(),
+                message,
+            });
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
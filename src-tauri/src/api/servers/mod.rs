@@ -1,2 +1,7 @@
+pub mod args;
+pub mod binary;
 pub mod core;
+pub mod env;
+pub mod package;
+pub mod paths;
 pub mod view;
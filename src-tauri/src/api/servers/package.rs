@@ -0,0 +1,293 @@
+use crate::utils::version::Version;
+use crate::APP_STATE_FILENAME;
+use anyhow::{anyhow, Result};
+use log::trace;
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri_plugin_store::StoreExt;
+
+const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+const PYPI_URL: &str = "https://pypi.org/pypi";
+
+/// `uvx` options whose value is a separate argument.
+const UVX_VALUE_OPTIONS: [&str; 9] = [
+    "--python",
+    "-p",
+    "--with",
+    "--with-editable",
+    "--with-requirements",
+    "--index",
+    "--index-url",
+    "--extra-index-url",
+    "--default-index",
+];
+
+/// Where a launcher fetches server packages from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageIndex {
+    Npm,
+    PyPI,
+}
+
+impl PackageIndex {
+    pub fn for_launcher(command: &str) -> Option<Self> {
+        match command {
+            "npx" | "bunx" => Some(PackageIndex::Npm),
+            "uvx" => Some(PackageIndex::PyPI),
+            _ => None,
+        }
+    }
+
+    /// Splits a package argument into its name and version, if it has one:
+    /// `@scope/name@1.2.3` for npm, `name==1.2.3` for PyPI. The URL of a PEP
+    /// 508 direct reference (`name @ git+https://…`) is not a version.
+    fn split<'a>(&self, spec: &'a str) -> (&'a str, Option<&'a str>) {
+        match self {
+            PackageIndex::Npm => {
+                // Skip the `@` of a scope.
+                match spec[1.min(spec.len())..].find('@') {
+                    Some(i) => (&spec[..i + 1], Some(&spec[i + 2..])),
+                    None => (spec, None),
+                }
+            }
+            PackageIndex::PyPI => match spec.split_once("==") {
+                Some((name, version)) => (name.trim(), Some(version.trim())),
+                None => match spec.split_once('@') {
+                    Some((name, _)) => (name.trim(), None),
+                    None => (spec, None),
+                },
+            },
+        }
+    }
+
+    fn pin(&self, name: &str, version: &str) -> String {
+        match self {
+            PackageIndex::Npm => format!("{}@{}", name, version),
+            PackageIndex::PyPI => format!("{}=={}", name, version),
+        }
+    }
+
+    /// Whether `spec` installs from a URL rather than a release of the index.
+    fn is_direct_reference(&self, spec: &str) -> bool {
+        *self == PackageIndex::PyPI && !spec.contains("==") && spec.contains('@')
+    }
+
+    /// Current release of `name`, as published by the index.
+    pub async fn latest_version(&self, name: &str) -> Result<String> {
+        let base = match self {
+            PackageIndex::Npm => NPM_REGISTRY_URL,
+            PackageIndex::PyPI => PYPI_URL,
+        };
+        self.latest_version_at(base, name).await
+    }
+
+    async fn latest_version_at(&self, base: &str, name: &str) -> Result<String> {
+        let url = match self {
+            // The registry takes a scoped name as a single path segment.
+            PackageIndex::Npm => format!(
+                "{}/-/package/{}/dist-tags",
+                base,
+                name.replacen('/', "%2F", 1)
+            ),
+            // Extras do not change the version.
+            PackageIndex::PyPI => {
+                let name = name.split('[').next().unwrap_or(name);
+                format!("{}/{}/json", base, name)
+            }
+        };
+        trace!("Fetching {}", url);
+        let metadata: Value = reqwest::get(&url).await?.error_for_status()?.json().await?;
+        let version = match self {
+            PackageIndex::Npm => &metadata["latest"],
+            PackageIndex::PyPI => &metadata["info"]["version"],
+        };
+        version
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| anyhow!("No published version of {}", name))
+    }
+}
+
+/// Position of the package in a launcher's arguments: the value of
+/// `--package`/`-p` (npm) or `--from` (uvx), otherwise the first argument that
+/// is not an option.
+fn package_position(index: PackageIndex, args: &[String]) -> Option<usize> {
+    let package_options: &[&str] = match index {
+        PackageIndex::Npm => &["--package", "-p"],
+        PackageIndex::PyPI => &["--from"],
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if package_options.contains(&arg) {
+            return (i + 1 < args.len()).then_some(i + 1);
+        }
+        if !arg.starts_with('-') {
+            return Some(i);
+        }
+        if index == PackageIndex::PyPI && UVX_VALUE_OPTIONS.contains(&arg) {
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Name of the package a launcher's arguments run, without its version.
+pub fn package_name(index: PackageIndex, args: &[String]) -> Option<String> {
+    let position = package_position(index, args)?;
+    Some(index.split(&args[position]).0.to_string())
+}
+
+/// Pins the package in `args` to `version`, or to the latest release when
+/// `version` is `None`. Packages the registry already pins to a release and
+/// PyPI direct references are left alone; dist-tags such as `@latest` are
+/// replaced. Returns the version the arguments now run.
+pub async fn pin_package(
+    index: PackageIndex,
+    args: &mut [String],
+    version: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(position) = package_position(index, args) else {
+        return Ok(None);
+    };
+    if index.is_direct_reference(&args[position]) {
+        return Ok(None);
+    }
+    let (name, pinned) = index.split(&args[position]);
+    if let Some(pinned) = pinned.filter(|pinned| Version::parse(pinned).is_some()) {
+        return Ok(Some(pinned.to_string()));
+    }
+    let version = match version {
+        Some(version) => version.to_string(),
+        None => index.latest_version(name).await?,
+    };
+    trace!("Pinning {} to {}", name, version);
+    args[position] = index.pin(name, &version);
+    Ok(Some(version))
+}
+
+/// Package versions servers were installed with, by server id.
+pub(crate) fn server_versions(app_handle: &tauri::AppHandle) -> Result<HashMap<String, String>> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    Ok(store
+        .get("server_versions")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default())
+}
+
+/// Records the version a server was installed with, or forgets it when
+/// `version` is `None`.
+pub(crate) fn set_server_version(
+    app_handle: &tauri::AppHandle,
+    server_id: &str,
+    version: Option<&str>,
+) -> Result<()> {
+    let store = app_handle.store(APP_STATE_FILENAME)?;
+    let mut versions = server_versions(app_handle)?;
+    match version {
+        Some(version) => versions.insert(server_id.to_string(), version.to_string()),
+        None => versions.remove(server_id),
+    };
+    store.set("server_versions", serde_json::to_value(versions)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Answers a single request with `body` on a local port. Returns the base
+    /// URL and a receiver for the request line.
+    fn serve_once(body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            sender.send(line.trim().to_string()).unwrap();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        (base, receiver)
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_package_versions() {
+        let npm = PackageIndex::Npm;
+        assert_eq!(
+            npm.split("@scope/name@1.2.3"),
+            ("@scope/name", Some("1.2.3"))
+        );
+        assert_eq!(npm.split("@scope/name"), ("@scope/name", None));
+        assert_eq!(npm.split("name@latest"), ("name", Some("latest")));
+
+        let pypi = PackageIndex::PyPI;
+        assert_eq!(pypi.split("name==1.2.3"), ("name", Some("1.2.3")));
+        assert_eq!(pypi.split("name[cli]"), ("name[cli]", None));
+        assert_eq!(
+            pypi.split("name @ git+https://example.com/name@v1"),
+            ("name", None)
+        );
+    }
+
+    #[tokio::test]
+    async fn leaves_direct_references_alone() {
+        let mut uvx_args = args(&["--from", "name @ git+https://example.com/name", "name"]);
+        let pinned = pin_package(PackageIndex::PyPI, &mut uvx_args, Some("1.0.0"))
+            .await
+            .unwrap();
+        assert_eq!(pinned, None);
+        assert_eq!(uvx_args[1], "name @ git+https://example.com/name");
+
+        let mut uvx_args = args(&["name"]);
+        let pinned = pin_package(PackageIndex::PyPI, &mut uvx_args, Some("1.0.0"))
+            .await
+            .unwrap();
+        assert_eq!(pinned.as_deref(), Some("1.0.0"));
+        assert_eq!(uvx_args, ["name==1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn encodes_scoped_npm_names() {
+        let (base, request) = serve_once(r#"{"latest":"2.0.0","next":"3.0.0-rc.1"}"#);
+        let version = PackageIndex::Npm
+            .latest_version_at(&base, "@scope/name")
+            .await
+            .unwrap();
+        assert_eq!(version, "2.0.0");
+        assert_eq!(
+            request.recv().unwrap(),
+            "GET /-/package/@scope%2Fname/dist-tags HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_pypi_version() {
+        let (base, request) = serve_once(r#"{"info":{"version":"0.4.1"}}"#);
+        let version = PackageIndex::PyPI
+            .latest_version_at(&base, "name[cli]")
+            .await
+            .unwrap();
+        assert_eq!(version, "0.4.1");
+        assert_eq!(request.recv().unwrap(), "GET /name/json HTTP/1.1");
+    }
+}
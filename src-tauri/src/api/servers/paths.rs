@@ -0,0 +1,266 @@
+use crate::api::clients::core::McpClient;
+use crate::utils::os::get_home;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories a server should rarely be pointed at: credentials and the MCP
+/// clients' own configuration, which lists every server and its secrets.
+fn sensitive_dirs(home: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = [".ssh", ".gnupg", ".aws"]
+        .into_iter()
+        .map(|name| home.join(name))
+        .collect();
+    for client in McpClient::ALL {
+        if let Some(dir) = client.config_path().ok().as_deref().and_then(Path::parent) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs.into_iter()
+        .filter_map(|dir| canonical(&dir).ok())
+        .collect()
+}
+
+/// Replaces `$VAR`, `${VAR}` and, on Windows, `%VAR%` with the value `lookup`
+/// gives for the variable. References to unset variables are kept as written, since `$` is a
+/// valid file name character as in `$RECYCLE.BIN`; their names are returned
+/// to explain a path that then does not exist.
+fn expand_vars(value: &str, lookup: impl Fn(&str) -> Option<String>) -> (String, Vec<String>) {
+    let mut expanded = String::new();
+    let mut unset = vec![];
+    let mut rest = value;
+    while let Some(start) = rest.find(['$', '%']) {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let reference = if rest[start..].starts_with('%') {
+            if cfg!(target_os = "windows") {
+                after
+                    .find('%')
+                    .filter(|end| *end > 0)
+                    .map(|end| (&after[..end], end + 1))
+            } else {
+                None
+            }
+        } else if let Some(braced) = after.strip_prefix('{') {
+            braced.find('}').map(|end| (&braced[..end], end + 2))
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            Some((&after[..end], end))
+        };
+        match reference {
+            Some((name, consumed)) if !name.is_empty() => {
+                match lookup(name) {
+                    Some(value) => expanded.push_str(&value),
+                    None => {
+                        expanded.push_str(&rest[start..start + 1 + consumed]);
+                        unset.push(name.to_string());
+                    }
+                }
+                rest = &after[consumed..];
+            }
+            _ => {
+                expanded.push_str(&rest[start..start + 1]);
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    (expanded, unset)
+}
+
+/// Resolves symlinks and `..`. Drops the `\\?\` prefix Windows adds, which
+/// many servers cannot handle.
+fn canonical(path: &Path) -> std::io::Result<PathBuf> {
+    let path = fs::canonicalize(path)?;
+    #[cfg(target_os = "windows")]
+    {
+        let text = path.to_string_lossy();
+        if let Some(stripped) = text.strip_prefix(r"\\?\") {
+            if !stripped.starts_with("UNC\\") {
+                return Ok(PathBuf::from(stripped));
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Turns a user supplied path into the absolute path a server is given:
+/// expands `~` and environment variables, resolves it and checks that it is
+/// an existing file or directory as `directory` requires. Returns the path
+/// and warnings about where it points.
+pub fn resolve_path(value: &str, directory: bool) -> Result<(String, Vec<String>), String> {
+    let home = get_home().map_err(|e| e.to_string())?;
+    resolve_path_in(value, directory, home, |name| std::env::var(name).ok())
+}
+
+fn resolve_path_in(
+    value: &str,
+    directory: bool,
+    home: PathBuf,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(String, Vec<String>), String> {
+    let (expanded, unset) = expand_vars(value.trim(), lookup);
+    let path = match expanded.strip_prefix('~') {
+        Some("") => home.clone(),
+        Some(rest) if rest.starts_with(['/', '\\']) => home.join(&rest[1..]),
+        _ => PathBuf::from(&expanded),
+    };
+    if !path.is_absolute() {
+        return Err(format!("{} is not an absolute path", value));
+    }
+    let path = canonical(&path).map_err(|_| match unset.as_slice() {
+        [] => format!("{} does not exist", value),
+        names => format!(
+            "{} does not exist; environment variable {} is not set",
+            value,
+            names.join(", ")
+        ),
+    })?;
+    if directory && !path.is_dir() {
+        return Err(format!("{} is not a directory", value));
+    }
+    if !directory && !path.is_file() {
+        return Err(format!("{} is not a file", value));
+    }
+
+    let mut warnings = vec![];
+    let home = canonical(&home).unwrap_or(home);
+    if !path.starts_with(&home) {
+        warnings.push(format!(
+            "{} is outside of your home directory",
+            path.to_string_lossy()
+        ));
+    }
+    let exposed: Vec<String> = sensitive_dirs(&home)
+        .into_iter()
+        .filter(|dir| path.starts_with(dir) || dir.starts_with(&path))
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect();
+    if !exposed.is_empty() {
+        warnings.push(format!(
+            "{} gives the server access to {}",
+            path.to_string_lossy(),
+            exposed.join(", ")
+        ));
+    }
+    Ok((path.to_string_lossy().to_string(), warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Resolves `value` with no environment variables set.
+    fn resolve(value: &str, directory: bool, home: &Path) -> Result<(String, Vec<String>), String> {
+        resolve_path_in(value, directory, home.to_path_buf(), |_| None)
+    }
+
+    fn resolved(path: &Path) -> String {
+        canonical(path).unwrap().to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn keeps_unset_variables_literal() {
+        let home = tempfile::tempdir().unwrap();
+        for name in ["$RECYCLE.BIN", "money$5", "a${b", "${MAGNET_TEST_UNSET}"] {
+            let dir = home.path().join(name);
+            fs::create_dir_all(&dir).unwrap();
+            let (path, warnings) = resolve(&dir.to_string_lossy(), true, home.path()).unwrap();
+            assert_eq!(path, resolved(&dir));
+            assert!(warnings.is_empty(), "{:?}", warnings);
+        }
+
+        let missing = home.path().join("$MAGNET_TEST_UNSET").join("data");
+        let error = resolve(&missing.to_string_lossy(), true, home.path()).unwrap_err();
+        assert!(error.ends_with("environment variable MAGNET_TEST_UNSET is not set"));
+    }
+
+    #[test]
+    fn expands_set_variables() {
+        let home = tempfile::tempdir().unwrap();
+        let dir = home.path().join("data");
+        fs::create_dir_all(&dir).unwrap();
+        let vars = HashMap::from([(
+            "MAGNET_TEST_HOME".to_string(),
+            home.path().to_string_lossy().to_string(),
+        )]);
+        for value in ["$MAGNET_TEST_HOME/data", "${MAGNET_TEST_HOME}/data"] {
+            let (path, _) = resolve_path_in(value, true, home.path().to_path_buf(), |name| {
+                vars.get(name).cloned()
+            })
+            .unwrap();
+            assert_eq!(path, resolved(&dir));
+        }
+    }
+
+    #[test]
+    fn expands_home() {
+        let home = tempfile::tempdir().unwrap();
+        let file = home.path().join("notes.txt");
+        fs::write(&file, "").unwrap();
+
+        assert_eq!(
+            resolve("~", true, home.path()).unwrap().0,
+            resolved(home.path())
+        );
+        assert_eq!(
+            resolve("~/notes.txt", false, home.path()).unwrap().0,
+            resolved(&file)
+        );
+        assert_eq!(
+            resolve("~/notes.txt", true, home.path()).unwrap_err(),
+            "~/notes.txt is not a directory"
+        );
+        assert_eq!(
+            resolve("notes.txt", false, home.path()).unwrap_err(),
+            "notes.txt is not an absolute path"
+        );
+    }
+
+    #[test]
+    fn warns_about_sensitive_dirs() {
+        let home = tempfile::tempdir().unwrap();
+        let ssh = home.path().join(".ssh");
+        fs::create_dir_all(&ssh).unwrap();
+        fs::create_dir_all(home.path().join("projects")).unwrap();
+
+        let (_, warnings) = resolve("~/.ssh", true, home.path()).unwrap();
+        assert_eq!(
+            warnings,
+            [format!(
+                "{} gives the server access to {}",
+                resolved(&ssh),
+                resolved(&ssh)
+            )]
+        );
+        // A parent of a sensitive directory exposes it too.
+        let (_, warnings) = resolve("~", true, home.path()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].ends_with(&resolved(&ssh)));
+
+        let (_, warnings) = resolve("~/projects", true, home.path()).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn warns_outside_home() {
+        let home = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let (path, warnings) = resolve(&other.path().to_string_lossy(), true, home.path()).unwrap();
+        assert_eq!(
+            warnings,
+            [format!("{} is outside of your home directory", path)]
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn strips_verbatim_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = canonical(dir.path()).unwrap();
+        assert!(!path.to_string_lossy().starts_with(r"\\?\"));
+        assert!(path.is_absolute());
+    }
+}
@@ -1,25 +1,44 @@
 use super::core::{
-    install_server_function, load_all_frontend_servers, load_all_installed_frontend_servers,
-    uninstall_server_function, update_server_function, FrontendServer,
+    check_server_updates_function, forget_unused_server, install_server_function,
+    load_all_frontend_servers, load_all_installed_frontend_servers, merge_input_values,
+    uninstall_server_function, update_server_function, upgrade_server_function, FrontendServer,
+    InstallReport, ServerUpdate,
 };
+use crate::api::clients::core::{target_clients, McpClient};
+use crate::error::MagnetResult;
 use log::debug;
 use std::collections::HashMap;
-use tauri_plugin_store::StoreExt;
 
 #[tauri::command]
-pub async fn get_servers(app_handle: tauri::AppHandle) -> Vec<FrontendServer> {
+pub async fn get_servers(app_handle: tauri::AppHandle) -> MagnetResult<Vec<FrontendServer>> {
     debug!("get_servers view");
     load_all_frontend_servers(&app_handle).await
 }
 
 #[tauri::command]
-pub async fn get_installed_servers(app_handle: tauri::AppHandle) -> Vec<FrontendServer> {
+pub async fn get_installed_servers(
+    app_handle: tauri::AppHandle,
+) -> MagnetResult<Vec<FrontendServer>> {
     load_all_installed_frontend_servers(&app_handle).await
 }
 
+/// `input_arg` fills the server's first input arg; `input_args` sets args by
+/// name.
 #[tauri::command]
-pub async fn install_server(app_handle: tauri::AppHandle, server_id: &str) -> Result<bool, String> {
-    Ok(install_server_function(&app_handle, server_id, None, None).await)
+pub async fn install_server(
+    app_handle: tauri::AppHandle,
+    server_id: &str,
+    env: Option<HashMap<String, String>>,
+    input_arg: Option<Vec<String>>,
+    input_args: Option<HashMap<String, Vec<String>>>,
+    clients: Option<Vec<McpClient>>,
+) -> MagnetResult<InstallReport> {
+    let clients = match clients {
+        Some(clients) => clients,
+        None => target_clients(&app_handle)?,
+    };
+    let input_args = merge_input_values(&app_handle, server_id, input_arg, input_args)?;
+    install_server_function(&app_handle, server_id, env, input_args, &clients).await
 }
 
 #[tauri::command]
@@ -28,11 +47,35 @@ pub async fn update_server(
     server_id: &str,
     env: Option<HashMap<String, String>>,
     input_arg: Option<Vec<String>>,
-) -> Result<bool, String> {
-    Ok(update_server_function(&app_handle, server_id, env, input_arg).await)
+    input_args: Option<HashMap<String, Vec<String>>>,
+    clients: Option<Vec<McpClient>>,
+) -> MagnetResult<InstallReport> {
+    let input_args = merge_input_values(&app_handle, server_id, input_arg, input_args)?;
+    update_server_function(&app_handle, server_id, env, input_args, clients.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn uninstall_server(
+    app_handle: tauri::AppHandle,
+    server_id: &str,
+    clients: Option<Vec<McpClient>>,
+) -> MagnetResult<()> {
+    uninstall_server_function(&app_handle, server_id, clients.as_deref()).await?;
+    forget_unused_server(&app_handle, server_id).await
+}
+
+/// Moves an installed server to its latest release. Returns the new version,
+/// if the server's package has one.
+#[tauri::command]
+pub async fn upgrade_server(
+    app_handle: tauri::AppHandle,
+    server_id: &str,
+) -> MagnetResult<Option<String>> {
+    upgrade_server_function(&app_handle, server_id).await
 }
 
+/// Installed servers with a newer release than the one they were installed at.
 #[tauri::command]
-pub async fn uninstall_server(server_id: &str) -> Result<bool, String> {
-    Ok(uninstall_server_function(server_id).await)
+pub async fn check_server_updates(app_handle: tauri::AppHandle) -> MagnetResult<Vec<ServerUpdate>> {
+    check_server_updates_function(&app_handle).await
 }
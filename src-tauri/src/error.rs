@@ -0,0 +1,113 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// Errors returned from Tauri commands.
+///
+/// Serialized to the frontend as `{"code": "...", "message": "..."}` so the UI
+/// can branch on `code` and show `message` to the user.
+#[derive(Debug, Error)]
+pub enum MagnetError {
+    #[error("Failed to parse {path}: {message}")]
+    ConfigParse { path: String, message: String },
+    #[error("Unknown server: {0}")]
+    UnknownServer(String),
+    #[error("Server {0} is not installed")]
+    ServerNotInstalled(String),
+    #[error("The server registry has not been downloaded yet")]
+    RegistryMissing,
+    #[error("The server registry is invalid: {0}")]
+    RegistryParse(String),
+    #[error("{0} is not installed")]
+    DependencyMissing(String),
+    #[error("{runtime} {required} or newer is required, found {found}")]
+    RuntimeOutdated {
+        runtime: String,
+        required: String,
+        found: String,
+    },
+    #[error("{dependency} is used by {}", servers.join(", "))]
+    DependencyInUse {
+        dependency: String,
+        servers: Vec<String>,
+    },
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Installation of {0} was cancelled")]
+    InstallCancelled(String),
+    #[error("Invalid settings: {}", .0.iter().map(|e| format!("{}: {}", e.field, e.message)).collect::<Vec<_>>().join(", "))]
+    Validation(Vec<FieldError>),
+    #[error("Inspector session {0} is not open")]
+    UnknownSession(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Store(#[from] tauri_plugin_store::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// A problem with one user-supplied setting, e.g. an env var.
+#[derive(Debug, Serialize, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl MagnetError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MagnetError::ConfigParse { .. } => "CONFIG_PARSE",
+            MagnetError::UnknownServer(_) => "UNKNOWN_SERVER",
+            MagnetError::ServerNotInstalled(_) => "SERVER_NOT_INSTALLED",
+            MagnetError::RegistryMissing => "REGISTRY_MISSING",
+            MagnetError::RegistryParse(_) => "REGISTRY_PARSE",
+            MagnetError::DependencyMissing(_) => "DEPENDENCY_MISSING",
+            MagnetError::RuntimeOutdated { .. } => "RUNTIME_OUTDATED",
+            MagnetError::DependencyInUse { .. } => "DEPENDENCY_IN_USE",
+            MagnetError::ChecksumMismatch { .. } => "CHECKSUM_MISMATCH",
+            MagnetError::InstallCancelled(_) => "INSTALL_CANCELLED",
+            MagnetError::Validation(_) => "VALIDATION",
+            MagnetError::UnknownSession(_) => "UNKNOWN_SESSION",
+            MagnetError::Io(_) => "IO",
+            MagnetError::Store(_) => "STORE",
+            MagnetError::Other(_) => "OTHER",
+        }
+    }
+}
+
+impl From<anyhow::Error> for MagnetError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<MagnetError>() {
+            Ok(error) => error,
+            Err(error) => MagnetError::Other(format!("{:#}", error)),
+        }
+    }
+}
+
+impl Serialize for MagnetError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = match self {
+            MagnetError::Validation(fields) => Some(fields),
+            _ => None,
+        };
+        let mut state =
+            serializer.serialize_struct("MagnetError", 2 + usize::from(fields.is_some()))?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        // Per-field errors, for forms to show next to each input.
+        if let Some(fields) = fields {
+            state.serialize_field("fields", fields)?;
+        }
+        state.end()
+    }
+}
+
+pub type MagnetResult<T> = Result<T, MagnetError>;
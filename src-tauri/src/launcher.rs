@@ -0,0 +1,162 @@
+use crate::utils::vault::Vault;
+use anyhow::{anyhow, Result};
+#[cfg(any(windows, test))]
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Flag that makes the Magnet executable start a server instead of the app.
+pub const LAUNCH_FLAG: &str = "--mcp-launch";
+
+/// Arguments that make a client start `command` through Magnet, which adds
+/// the server's secrets from the vault to its environment.
+pub fn launcher_args(server_id: &str, command: String, args: Vec<String>) -> Vec<String> {
+    let mut launcher_args = vec![
+        LAUNCH_FLAG.to_string(),
+        server_id.to_string(),
+        "--".to_string(),
+        command,
+    ];
+    launcher_args.extend(args);
+    launcher_args
+}
+
+/// The executable clients start servers through. An AppImage runs from a
+/// mount that changes on every start, so the image itself is used instead.
+pub fn launcher_path() -> Result<PathBuf> {
+    Ok(launcher_path_from(
+        std::env::var_os("APPIMAGE"),
+        std::env::current_exe()?,
+    ))
+}
+
+fn launcher_path_from(appimage: Option<OsString>, current_exe: PathBuf) -> PathBuf {
+    match appimage.filter(|path| !path.is_empty()) {
+        Some(appimage) => PathBuf::from(appimage),
+        None => current_exe,
+    }
+}
+
+/// Finds a bare program name in the `path` directories, trying each of the
+/// `extensions` listed as in `PATHEXT`. `Command` only tries `.exe` on
+/// Windows, which misses scripts such as `npx.cmd`.
+#[cfg(any(windows, test))]
+fn find_program(program: &str, path: Option<&OsStr>, extensions: &str) -> Option<PathBuf> {
+    let name = std::path::Path::new(program);
+    if name.extension().is_some() || name.components().count() != 1 {
+        return None;
+    }
+    std::env::split_paths(path?).find_map(|dir| {
+        extensions
+            .split(';')
+            .filter(|extension| !extension.is_empty())
+            .map(|extension| dir.join(format!("{}{}", program, extension)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn launch(args: &[String]) -> Result<i32> {
+    let (server_id, command) = match args {
+        [server_id, separator, command @ ..] if separator == "--" && !command.is_empty() => {
+            (server_id, command)
+        }
+        _ => {
+            return Err(anyhow!(
+                "Usage: {} <server id> -- <command> [args...]",
+                LAUNCH_FLAG
+            ))
+        }
+    };
+    let secrets = Vault::open()?.get(server_id)?;
+    #[cfg(windows)]
+    let program = find_program(
+        &command[0],
+        std::env::var_os("PATH").as_deref(),
+        &std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string()),
+    )
+    .map(PathBuf::into_os_string)
+    .unwrap_or_else(|| command[0].clone().into());
+    #[cfg(not(windows))]
+    let program = &command[0];
+    let mut child = Command::new(program);
+    child.args(&command[1..]).envs(secrets);
+
+    #[cfg(unix)]
+    {
+        // Hand the process over so signals from the client reach the server.
+        use std::os::unix::process::CommandExt;
+        let error = child.exec();
+        Err(anyhow!("Failed to start {}: {}", command[0], error))
+    }
+    #[cfg(not(unix))]
+    {
+        let status = child
+            .status()
+            .map_err(|e| anyhow!("Failed to start {}: {}", command[0], e))?;
+        Ok(status.code().unwrap_or(1))
+    }
+}
+
+/// Entry point of launcher mode, given the arguments after [`LAUNCH_FLAG`].
+/// Errors go to stderr, which MCP clients show in their server logs.
+pub fn run_launcher(args: &[String]) -> i32 {
+    match launch(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("magnet: {:#}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_scripts_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(second.join("npx.cmd"), "").unwrap();
+        fs::write(second.join("node.exe"), "").unwrap();
+        let path = std::env::join_paths([&first, &second]).unwrap();
+
+        assert_eq!(
+            find_program("npx", Some(&path), ".EXE;.cmd"),
+            Some(second.join("npx.cmd"))
+        );
+        assert_eq!(
+            find_program("node", Some(&path), ".exe;.cmd"),
+            Some(second.join("node.exe"))
+        );
+        assert_eq!(find_program("uvx", Some(&path), ".exe;.cmd"), None);
+        assert_eq!(find_program("npx", None, ".cmd"), None);
+    }
+
+    #[test]
+    fn keeps_paths_and_names_with_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("npx.cmd"), "").unwrap();
+        let path = std::env::join_paths([dir.path()]).unwrap();
+
+        assert_eq!(find_program("npx.cmd", Some(&path), ".cmd"), None);
+        let explicit = dir.path().join("npx").to_string_lossy().to_string();
+        assert_eq!(find_program(&explicit, Some(&path), ".cmd"), None);
+    }
+
+    #[test]
+    fn launcher_path_prefers_appimage() {
+        let exe = PathBuf::from("/tmp/.mount_Magnet/usr/bin/magnet-desktop");
+        assert_eq!(
+            launcher_path_from(Some("/opt/Magnet.AppImage".into()), exe.clone()),
+            PathBuf::from("/opt/Magnet.AppImage")
+        );
+        assert_eq!(launcher_path_from(Some("".into()), exe.clone()), exe);
+        assert_eq!(launcher_path_from(None, exe.clone()), exe);
+    }
+}
@@ -1,12 +1,21 @@
 mod api;
+mod error;
+mod launcher;
 mod utils;
 
+pub use launcher::{run_launcher, LAUNCH_FLAG};
+
 use tauri_plugin_log::{Target, TargetKind};
-use tauri_plugin_store::StoreExt;
 
 pub const APP_STATE_FILENAME: &str = "AppState.json";
 
+use api::clients::view as clients_view;
 use api::dependency::view as dependency_view;
+use api::dependency::core::{Dependency, InstallState};
+use api::dependency::resolver::resolve;
+use api::mcp::core::InspectorState;
+use api::mcp::view as mcp_view;
+use api::servers::core::repoint_launchers;
 use api::servers::view as servers_view;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -26,16 +35,64 @@ pub fn run() {
                 .build(),
         )
         .plugin(tauri_plugin_store::Builder::new().build())
+        .manage(InspectorState::default())
+        .manage(InstallState::default())
+        .setup(|app| {
+            if let Err(e) = repoint_launchers() {
+                log::warn!("Failed to update the launcher path: {}", e);
+            }
+            // Runtimes can be removed or upgraded while Magnet is closed.
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                for dependency in Dependency::ALL {
+                    if let Err(e) = resolve(&app_handle, dependency).await {
+                        log::warn!("Failed to resolve {}: {}", dependency.display_name(), e);
+                    }
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            clients_view::get_clients,
+            clients_view::set_target_clients,
+            clients_view::list_config_backups,
+            clients_view::restore_config_backup,
             dependency_view::check_dependency,
             dependency_view::check_resource,
+            dependency_view::get_registry_status,
+            dependency_view::set_registry_ttl,
+            dependency_view::get_registry_sources,
+            dependency_view::set_registry_sources,
+            dependency_view::acknowledge_registry_override,
             dependency_view::install_npm,
             dependency_view::install_uv,
+            dependency_view::install_bun,
+            dependency_view::install_deno,
+            dependency_view::cancel_install,
+            dependency_view::uninstall_node,
+            dependency_view::uninstall_uv,
+            dependency_view::uninstall_bun,
+            dependency_view::uninstall_deno,
+            dependency_view::repair_dependency,
+            dependency_view::list_dependency_versions,
+            dependency_view::switch_dependency_version,
+            mcp_view::test_server,
+            mcp_view::open_inspector_session,
+            mcp_view::close_inspector_session,
+            mcp_view::list_inspector_tools,
+            mcp_view::call_inspector_tool,
+            mcp_view::list_inspector_resources,
+            mcp_view::read_inspector_resource,
+            mcp_view::list_inspector_prompts,
+            mcp_view::get_inspector_prompt,
+            mcp_view::get_inspector_stderr,
             servers_view::get_servers,
             servers_view::get_installed_servers,
             servers_view::install_server,
             servers_view::uninstall_server,
             servers_view::update_server,
+            servers_view::upgrade_server,
+            servers_view::check_server_updates,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
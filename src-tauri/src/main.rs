@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(magnet_desktop_lib::LAUNCH_FLAG) {
+        std::process::exit(magnet_desktop_lib::run_launcher(&args[2..]));
+    }
     magnet_desktop_lib::run()
 }
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use log::trace;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tar::Archive;
+use zip::ZipArchive;
+
+/// Unpacks a `.tar.gz` or `.zip` archive into `dest` one entry at a time.
+/// `on_entry` is called with the number of entries written so far after each
+/// one, and aborts the extraction by returning an error.
+pub fn extract<F>(archive: &Path, dest: &Path, mut on_entry: F) -> Result<u64>
+where
+    F: FnMut(u64) -> Result<()>,
+{
+    let name = archive.to_string_lossy().to_lowercase();
+    fs::create_dir_all(dest)?;
+    if name.ends_with(".zip") {
+        extract_zip(archive, dest, &mut on_entry)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        extract_tar_gz(archive, dest, &mut on_entry)
+    } else {
+        Err(anyhow!("Unsupported archive format: {}", name))
+    }
+}
+
+fn extract_tar_gz(
+    archive: &Path,
+    dest: &Path,
+    on_entry: &mut dyn FnMut(u64) -> Result<()>,
+) -> Result<u64> {
+    let mut archive = Archive::new(GzDecoder::new(fs::File::open(archive)?));
+    archive.set_preserve_permissions(true);
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // `unpack_in` refuses entries that would escape `dest`.
+        if entry.unpack_in(dest)? {
+            count += 1;
+            on_entry(count)?;
+        }
+    }
+    trace!(
+        "Extracted {} entries into {}",
+        count,
+        dest.to_string_lossy()
+    );
+    Ok(count)
+}
+
+fn extract_zip(
+    archive: &Path,
+    dest: &Path,
+    on_entry: &mut dyn FnMut(u64) -> Result<()>,
+) -> Result<u64> {
+    let mut archive = ZipArchive::new(fs::File::open(archive)?)?;
+    let mut count = 0;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let Some(relative) = file.enclosed_name().map(|p| p.to_path_buf()) else {
+            trace!("Skipping unsafe zip entry {}", file.name());
+            continue;
+        };
+        let path = dest.join(relative);
+        if file.is_dir() {
+            fs::create_dir_all(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut file, &mut fs::File::create(&path)?)?;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = file.unix_mode() {
+                fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        count += 1;
+        on_entry(count)?;
+    }
+    trace!(
+        "Extracted {} entries into {}",
+        count,
+        dest.to_string_lossy()
+    );
+    Ok(count)
+}
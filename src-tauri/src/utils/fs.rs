@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use log::trace;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Replaces `path` with `contents` without ever leaving a partially written
+/// file behind: the data is written and fsynced to a temporary file in the
+/// same directory, which is then renamed over the target.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    write_atomic_with(path, contents, false)
+}
+
+/// Like [`write_atomic`], for files only the user may read: on Unix the
+/// temporary file is created with mode `0600`, so the contents are never
+/// readable by others, even before the rename.
+pub fn write_atomic_private(path: &Path, contents: &[u8]) -> Result<()> {
+    write_atomic_with(path, contents, true)
+}
+
+fn write_atomic_with(path: &Path, contents: &[u8], private: bool) -> Result<()> {
+    // Write through symlinks so dotfile setups keep pointing at the real file.
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path
+        .parent()
+        .context("Failed to get parent directory of target file")?;
+    fs::create_dir_all(dir)?;
+
+    let mut builder = tempfile::Builder::new();
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o600));
+    }
+    let mut temp_file = builder.tempfile_in(dir)?;
+    temp_file.write_all(contents)?;
+    temp_file.as_file().sync_all()?;
+    if !private {
+        if let Ok(metadata) = fs::metadata(&path) {
+            temp_file
+                .as_file()
+                .set_permissions(metadata.permissions())?;
+        }
+    }
+    temp_file
+        .persist(&path)
+        .with_context(|| format!("Failed to replace {}", path.to_string_lossy()))?;
+
+    #[cfg(unix)]
+    {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    trace!("Atomically wrote {}", path.to_string_lossy());
+    Ok(())
+}
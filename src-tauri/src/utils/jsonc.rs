@@ -0,0 +1,596 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// One step of a path into a document.
+#[derive(Clone, Copy)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+    /// The element of an array that is an object whose member `.0` is the
+    /// string `.1`, or the end of the array when there is none.
+    Where(&'a str, &'a str),
+}
+
+/// Parses JSON that may contain `//` and `/* */` comments and trailing
+/// commas, as VS Code and Zed settings do.
+pub fn parse(text: &str) -> serde_json::Result<Value> {
+    serde_json::from_str(&strip(text))
+}
+
+/// Sets the value at `path`, or removes it when `value` is `None`, and
+/// returns the new text. Only the edited value changes: comments, formatting
+/// and everything else in `text` are kept. Missing parents are created.
+pub fn set(text: &str, path: &[Segment], value: Option<&Value>) -> Result<String> {
+    let uncommented = strip_comments(text);
+    let stripped = strip_trailing_commas(uncommented.clone());
+    let s = stripped.as_bytes();
+    let start = skip_ws(s, 0);
+    if start == s.len() {
+        return Ok(match value {
+            Some(value) => format!("{}\n", render(&nest(path, value), "")),
+            None => text.to_string(),
+        });
+    }
+    let mut span = (start, value_end(s, start).ok_or_else(malformed)?);
+    for (depth, segment) in path.iter().enumerate() {
+        let last = depth + 1 == path.len();
+        let segment = match (*segment, s[span.0]) {
+            (Segment::Where(key, wanted), b'[') => {
+                let elements = elements(s, span.0).ok_or_else(malformed)?;
+                let index = elements
+                    .iter()
+                    .position(|&(start, _)| has_member(s, &stripped, start, key, wanted))
+                    .unwrap_or(elements.len());
+                Segment::Index(index)
+            }
+            (segment, _) => segment,
+        };
+        match (segment, s[span.0]) {
+            (Segment::Key(key), b'{') => {
+                let members = members(s, &stripped, span.0).ok_or_else(malformed)?;
+                let Some(position) = members.iter().position(|member| member.key == *key) else {
+                    let Some(value) = value else {
+                        return Ok(text.to_string());
+                    };
+                    let entry = format!(
+                        "{}: {}",
+                        json!(key),
+                        render(
+                            &nest(&path[depth + 1..], value),
+                            &child_indent(text, span.0)
+                        )
+                    );
+                    let items: Vec<(usize, usize)> = members
+                        .iter()
+                        .map(|member| (member.start, member.value.1))
+                        .collect();
+                    return Ok(insert_item(text, span, &items, b'{', &entry));
+                };
+                if !last {
+                    span = members[position].value;
+                    continue;
+                }
+                let items: Vec<(usize, usize)> = members
+                    .iter()
+                    .map(|member| (member.start, member.value.1))
+                    .collect();
+                return Ok(match value {
+                    Some(value) => replace(text, members[position].value, value),
+                    None => remove_item(text, &uncommented, &items, position),
+                });
+            }
+            (Segment::Index(index), b'[') => {
+                let elements = elements(s, span.0).ok_or_else(malformed)?;
+                if index >= elements.len() {
+                    let Some(value) = value else {
+                        return Ok(text.to_string());
+                    };
+                    if index > elements.len() {
+                        return Err(anyhow!("Index {} is past the end of the array", index));
+                    }
+                    let entry = render(
+                        &nest(&path[depth + 1..], value),
+                        &child_indent(text, span.0),
+                    );
+                    return Ok(insert_item(text, span, &elements, b'[', &entry));
+                }
+                if !last {
+                    span = elements[index];
+                    continue;
+                }
+                return Ok(match value {
+                    Some(value) => replace(text, elements[index], value),
+                    None => remove_item(text, &uncommented, &elements, index),
+                });
+            }
+            _ => {
+                let Some(value) = value else {
+                    return Ok(text.to_string());
+                };
+                return Ok(replace(text, span, &nest(&path[depth..], value)));
+            }
+        }
+    }
+    Ok(match value {
+        Some(value) => replace(text, span, value),
+        None => text.to_string(),
+    })
+}
+
+fn malformed() -> anyhow::Error {
+    anyhow!("The document is not valid JSON")
+}
+
+/// `text` with comments and trailing commas blanked out with spaces, so
+/// offsets into the result are offsets into `text`.
+fn strip(text: &str) -> String {
+    strip_trailing_commas(strip_comments(text))
+}
+
+/// `text` with comments blanked out with spaces.
+fn strip_comments(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        if in_string {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'"', _) => in_string = true,
+            (b'/', Some(b'/')) => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            (b'/', Some(b'*')) => {
+                let end = text[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                for byte in &mut out[i..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    out
+}
+
+/// `out`, already without comments, with trailing commas blanked out.
+fn strip_trailing_commas(mut out: Vec<u8>) -> String {
+    let mut in_string = false;
+    let mut comma = None;
+    let mut i = 0;
+    while i < out.len() {
+        if in_string {
+            match out[i] {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match out[i] {
+            b'"' => {
+                in_string = true;
+                comma = None;
+            }
+            b',' => comma = Some(i),
+            b'}' | b']' => {
+                if let Some(comma) = comma.take() {
+                    out[comma] = b' ';
+                }
+            }
+            byte if byte.is_ascii_whitespace() => {}
+            _ => comma = None,
+        }
+        i += 1;
+    }
+    // Only whole characters were blanked, so this never replaces anything.
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn skip_ws(s: &[u8], mut i: usize) -> usize {
+    while i < s.len() && s[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn string_end(s: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < s.len() {
+        match s[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// End of the value starting at `start`.
+fn value_end(s: &[u8], start: usize) -> Option<usize> {
+    match s.get(start)? {
+        b'"' => string_end(s, start),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut i = start;
+            while i < s.len() {
+                match s[i] {
+                    b'"' => {
+                        i = string_end(s, i)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            None
+        }
+        _ => {
+            let mut i = start;
+            while i < s.len() && !matches!(s[i], b',' | b'}' | b']') && !s[i].is_ascii_whitespace()
+            {
+                i += 1;
+            }
+            (i > start).then_some(i)
+        }
+    }
+}
+
+struct Member {
+    key: String,
+    start: usize,
+    value: (usize, usize),
+}
+
+/// Members of the object opening at `open`.
+fn members(s: &[u8], stripped: &str, open: usize) -> Option<Vec<Member>> {
+    let mut members = vec![];
+    let mut i = skip_ws(s, open + 1);
+    while *s.get(i)? != b'}' {
+        let key_end = string_end(s, i)?;
+        let key: String = serde_json::from_str(&stripped[i..key_end]).ok()?;
+        let colon = skip_ws(s, key_end);
+        if *s.get(colon)? != b':' {
+            return None;
+        }
+        let value_start = skip_ws(s, colon + 1);
+        let end = value_end(s, value_start)?;
+        members.push(Member {
+            key,
+            start: i,
+            value: (value_start, end),
+        });
+        i = skip_ws(s, end);
+        if *s.get(i)? == b',' {
+            i = skip_ws(s, i + 1);
+        }
+    }
+    Some(members)
+}
+
+/// Whether the value at `start` is an object whose member `key` is the
+/// string `wanted`.
+fn has_member(s: &[u8], stripped: &str, start: usize, key: &str, wanted: &str) -> bool {
+    if s[start] != b'{' {
+        return false;
+    }
+    members(s, stripped, start).is_some_and(|members| {
+        members.iter().any(|member| {
+            member.key == key
+                && serde_json::from_str::<String>(&stripped[member.value.0..member.value.1])
+                    .is_ok_and(|value| value == wanted)
+        })
+    })
+}
+
+/// Elements of the array opening at `open`.
+fn elements(s: &[u8], open: usize) -> Option<Vec<(usize, usize)>> {
+    let mut elements = vec![];
+    let mut i = skip_ws(s, open + 1);
+    while *s.get(i)? != b']' {
+        let end = value_end(s, i)?;
+        elements.push((i, end));
+        i = skip_ws(s, end);
+        if *s.get(i)? == b',' {
+            i = skip_ws(s, i + 1);
+        }
+    }
+    Some(elements)
+}
+
+/// The value that puts `value` at `path` inside an empty container.
+fn nest(path: &[Segment], value: &Value) -> Value {
+    path.iter()
+        .rev()
+        .fold(value.clone(), |inner, segment| match segment {
+            Segment::Key(key) => {
+                let mut object = serde_json::Map::new();
+                object.insert(key.to_string(), inner);
+                Value::Object(object)
+            }
+            Segment::Index(_) | Segment::Where(..) => json!([inner]),
+        })
+}
+
+/// Leading whitespace of the line holding `position`.
+fn line_indent(text: &str, position: usize) -> String {
+    let line_start = text[..position].rfind('\n').map_or(0, |i| i + 1);
+    text[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+fn child_indent(text: &str, open: usize) -> String {
+    format!("{}  ", line_indent(text, open))
+}
+
+/// `value` pretty printed, with its lines after the first indented by
+/// `indent`.
+fn render(value: &Value, indent: &str) -> String {
+    let pretty = serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+    pretty.replace('\n', &format!("\n{}", indent))
+}
+
+fn replace(text: &str, span: (usize, usize), value: &Value) -> String {
+    let rendered = render(value, &line_indent(text, span.0));
+    format!("{}{}{}", &text[..span.0], rendered, &text[span.1..])
+}
+
+/// Adds `entry` after the last of `items`, the spans of the container's
+/// members or elements.
+fn insert_item(
+    text: &str,
+    container: (usize, usize),
+    items: &[(usize, usize)],
+    open: u8,
+    entry: &str,
+) -> String {
+    match items.last() {
+        Some(&(start, end)) => format!(
+            "{},\n{}{}{}",
+            &text[..end],
+            line_indent(text, start),
+            entry,
+            &text[end..]
+        ),
+        None => {
+            let indent = line_indent(text, container.0);
+            let close = if open == b'{' { '}' } else { ']' };
+            format!(
+                "{}{}\n{}  {}\n{}{}{}",
+                &text[..container.0],
+                open as char,
+                indent,
+                entry,
+                indent,
+                close,
+                &text[container.1..]
+            )
+        }
+    }
+}
+
+/// Removes the item at `position` of `items` along with the comma that
+/// separates it from its neighbours, and its line when it has one to itself.
+/// Comments around it are kept. `uncommented` is `text` with only its
+/// comments blanked, so trailing commas can still be found.
+fn remove_item(
+    text: &str,
+    uncommented: &[u8],
+    items: &[(usize, usize)],
+    position: usize,
+) -> String {
+    let (start, mut end) = items[position];
+    let mut separator = None;
+    let after = skip_ws(uncommented, end);
+    if uncommented.get(after) == Some(&b',') {
+        end = after + 1;
+    } else if position > 0 {
+        let before = skip_ws(uncommented, items[position - 1].1);
+        if uncommented.get(before) == Some(&b',') {
+            separator = Some(before);
+        }
+    }
+
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i);
+    let (start, end) = if line_start > 0
+        && text[line_start..start].trim().is_empty()
+        && text[end..line_end].trim().is_empty()
+    {
+        (line_start - 1, line_end)
+    } else {
+        (start, end)
+    };
+
+    let mut edited = format!("{}{}", &text[..start], &text[end..]);
+    if let Some(separator) = separator {
+        edited.remove(separator);
+    }
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = r#"{
+  // Editor settings
+  "theme": "One Dark", /* trailing comma below */
+  "context_servers": {
+    "existing": { "command": { "path": "a" } },
+  },
+}
+"#;
+
+    #[test]
+    fn parses_comments_and_trailing_commas() {
+        let value = parse(SETTINGS).unwrap();
+        assert_eq!(value["theme"], "One Dark");
+        assert_eq!(value["context_servers"]["existing"]["command"]["path"], "a");
+    }
+
+    #[test]
+    fn keeps_comments_when_adding_a_member() {
+        let text = set(
+            SETTINGS,
+            &[Segment::Key("context_servers"), Segment::Key("new")],
+            Some(&json!({ "command": { "path": "b" } })),
+        )
+        .unwrap();
+        assert!(text.contains("// Editor settings"));
+        assert!(text.contains("/* trailing comma below */"));
+        let value = parse(&text).unwrap();
+        assert_eq!(value["context_servers"]["new"]["command"]["path"], "b");
+        assert_eq!(value["context_servers"]["existing"]["command"]["path"], "a");
+    }
+
+    #[test]
+    fn replaces_and_removes_members() {
+        let path = [Segment::Key("context_servers"), Segment::Key("existing")];
+        let text = set(
+            SETTINGS,
+            &path,
+            Some(&json!({ "command": { "path": "c" } })),
+        )
+        .unwrap();
+        assert_eq!(
+            parse(&text).unwrap()["context_servers"]["existing"]["command"]["path"],
+            "c"
+        );
+        let text = set(&text, &path, None).unwrap();
+        let value = parse(&text).unwrap();
+        assert_eq!(value["context_servers"], json!({}));
+        assert_eq!(value["theme"], "One Dark");
+        assert!(text.contains("// Editor settings"));
+    }
+
+    #[test]
+    fn creates_missing_parents() {
+        let text = set(
+            "{ \"a\": 1 }",
+            &[
+                Segment::Key("experimental"),
+                Segment::Key("servers"),
+                Segment::Index(0),
+            ],
+            Some(&json!({ "name": "x" })),
+        )
+        .unwrap();
+        let value = parse(&text).unwrap();
+        assert_eq!(value["a"], 1);
+        assert_eq!(value["experimental"]["servers"][0]["name"], "x");
+    }
+
+    #[test]
+    fn edits_array_elements() {
+        let text = "{\"s\": [{\"name\": \"a\"}, {\"name\": \"b\"}]}";
+        let text = set(
+            text,
+            &[Segment::Key("s"), Segment::Index(2)],
+            Some(&json!({ "name": "c" })),
+        )
+        .unwrap();
+        let text = set(&text, &[Segment::Key("s"), Segment::Index(0)], None).unwrap();
+        assert_eq!(
+            parse(&text).unwrap()["s"],
+            json!([{ "name": "b" }, { "name": "c" }])
+        );
+    }
+
+    #[test]
+    fn keeps_comments_when_removing_the_only_member() {
+        let text = r#"{
+  "mcpServers": {
+    // Added by Magnet
+    "files": { "command": "npx" }, // the only server
+    /* more to come */
+  }
+}
+"#;
+        let text = set(
+            text,
+            &[Segment::Key("mcpServers"), Segment::Key("files")],
+            None,
+        )
+        .unwrap();
+        assert!(text.contains("// Added by Magnet"));
+        assert!(text.contains("// the only server"));
+        assert!(text.contains("/* more to come */"));
+        assert_eq!(parse(&text).unwrap()["mcpServers"], json!({}));
+    }
+
+    #[test]
+    fn removes_members_with_their_commas() {
+        let text = "{\n  \"a\": 1,\n  // about b\n  \"b\": 2,\n  \"c\": 3\n}";
+        let removed = set(text, &[Segment::Key("b")], None).unwrap();
+        assert_eq!(removed, "{\n  \"a\": 1,\n  // about b\n  \"c\": 3\n}");
+        let removed = set(text, &[Segment::Key("c")], None).unwrap();
+        assert_eq!(removed, "{\n  \"a\": 1,\n  // about b\n  \"b\": 2\n}");
+        let removed = set("[1, /* two */ 2]", &[Segment::Index(1)], None).unwrap();
+        assert_eq!(removed, "[1 /* two */ ]");
+        assert_eq!(parse(&removed).unwrap(), json!([1]));
+    }
+
+    #[test]
+    fn finds_array_elements_by_member() {
+        let text = r#"{"s": [{"name": "a", "v": 1}, {"name": "b", "v": 2}]}"#;
+        let path = [
+            Segment::Key("s"),
+            Segment::Where("name", "b"),
+            Segment::Key("v"),
+        ];
+        let text = set(text, &path, Some(&json!(3))).unwrap();
+        assert_eq!(parse(&text).unwrap()["s"][1]["v"], 3);
+        let text = set(
+            &text,
+            &[Segment::Key("s"), Segment::Where("name", "c")],
+            Some(&json!({ "name": "c" })),
+        )
+        .unwrap();
+        assert_eq!(parse(&text).unwrap()["s"][2]["name"], "c");
+        let text = set(
+            &text,
+            &[Segment::Key("s"), Segment::Where("name", "a")],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            parse(&text).unwrap()["s"],
+            json!([{ "name": "b", "v": 3 }, { "name": "c" }])
+        );
+    }
+
+    #[test]
+    fn keeps_comment_like_text_in_strings() {
+        let text = "{\"url\": \"https://example.com/*x*/\", }";
+        assert_eq!(parse(text).unwrap()["url"], "https://example.com/*x*/");
+    }
+}
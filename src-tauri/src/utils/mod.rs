@@ -1 +1,6 @@
+pub mod archive;
+pub mod fs;
+pub mod jsonc;
 pub mod os;
+pub mod vault;
+pub mod version;
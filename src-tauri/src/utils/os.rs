@@ -12,7 +12,7 @@ pub fn get_home() -> Result<PathBuf> {
 }
 
 pub fn detect_shell() -> Result<String> {
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     {
         let shell = std::env::var("SHELL").context("Failed to get SHELL environment variable")?;
         let shell_name = std::path::Path::new(&shell)
@@ -29,3 +29,40 @@ pub fn detect_shell() -> Result<String> {
         Ok("powershell".to_string())
     }
 }
+
+/// Per-user application configuration directory: `~/Library/Application Support`
+/// on macOS, `%APPDATA%` on Windows and `$XDG_CONFIG_HOME` (or `~/.config`) on Linux.
+pub fn get_config_dir() -> Result<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(get_home()?.join("Library").join("Application Support"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let appdata =
+            std::env::var("APPDATA").context("Failed to get APPDATA environment variable")?;
+        Ok(PathBuf::from(appdata))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match std::env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+            _ => Ok(get_home()?.join(".config")),
+        }
+    }
+}
+
+/// Directory where Magnet keeps its own files, such as config backups.
+pub fn get_magnet_dir() -> Result<PathBuf> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        Ok(get_home()?.join(".magnet"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Ok(get_home()?.join("AppData").join("Local").join("magnet"))
+    }
+}
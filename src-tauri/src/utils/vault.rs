@@ -0,0 +1,252 @@
+use super::fs::{write_atomic, write_atomic_private};
+use super::os::get_magnet_dir;
+use anyhow::{anyhow, Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+const VAULT_FILENAME: &str = "vault.json";
+const KEYRING_SERVICE: &str = "magnet";
+const KEYRING_USER: &str = "vault-key";
+/// Keeps the vault key in this file instead of the OS keyring, for tests and
+/// systems without a keyring.
+const KEY_FILE_ENV: &str = "MAGNET_VAULT_KEY_FILE";
+
+/// Where the key encrypting the vault is kept.
+enum KeyBackend {
+    Keyring,
+    File(PathBuf),
+}
+
+impl KeyBackend {
+    /// The key file named by `MAGNET_VAULT_KEY_FILE`, if set, else the keyring.
+    fn from_key_file(key_file: Option<OsString>) -> Self {
+        match key_file {
+            Some(path) => KeyBackend::File(PathBuf::from(path)),
+            None => KeyBackend::Keyring,
+        }
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        let encoded = match self {
+            KeyBackend::Keyring => {
+                match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.get_password() {
+                    Ok(encoded) => encoded,
+                    Err(keyring::Error::NoEntry) => return Ok(None),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            KeyBackend::File(path) if !path.exists() => return Ok(None),
+            KeyBackend::File(path) => fs::read_to_string(path)?,
+        };
+        Ok(Some(
+            hex::decode(encoded.trim()).context("The vault key is corrupted")?,
+        ))
+    }
+
+    fn save(&self, key: &[u8]) -> Result<()> {
+        let encoded = hex::encode(key);
+        match self {
+            KeyBackend::Keyring => {
+                keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.set_password(&encoded)?
+            }
+            KeyBackend::File(path) => write_atomic_private(path, encoded.as_bytes())?,
+        }
+        Ok(())
+    }
+}
+
+/// On-disk form of the vault: the JSON of every secret, encrypted as a whole.
+#[derive(Serialize, Deserialize)]
+struct SealedVault {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Server secrets encrypted with ChaCha20-Poly1305, by server id and then
+/// env var name. The file lives in the Magnet directory; its key lives in the
+/// OS keyring and is created on first write.
+pub struct Vault {
+    path: PathBuf,
+    key_backend: KeyBackend,
+}
+
+impl Vault {
+    pub fn open() -> Result<Self> {
+        Ok(Vault {
+            path: get_magnet_dir()?.join(VAULT_FILENAME),
+            key_backend: KeyBackend::from_key_file(std::env::var_os(KEY_FILE_ENV)),
+        })
+    }
+
+    /// A vault in `dir` whose key is kept in a file next to it.
+    #[cfg(test)]
+    pub(crate) fn with_key_file(dir: &std::path::Path) -> Self {
+        Vault {
+            path: dir.join(VAULT_FILENAME),
+            key_backend: KeyBackend::File(dir.join("vault.key")),
+        }
+    }
+
+    fn cipher(&self, create: bool) -> Result<Option<ChaCha20Poly1305>> {
+        let key = match self.key_backend.load()? {
+            Some(key) => key,
+            None if create => {
+                trace!("Creating vault key");
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+                self.key_backend.save(&key)?;
+                key
+            }
+            None => return Ok(None),
+        };
+        if key.len() != 32 {
+            return Err(anyhow!("The vault key is corrupted"));
+        }
+        Ok(Some(ChaCha20Poly1305::new(Key::from_slice(&key))))
+    }
+
+    fn read(&self) -> Result<HashMap<String, HashMap<String, String>>> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let sealed: SealedVault = serde_json::from_slice(&fs::read(&self.path)?)?;
+        let cipher = self
+            .cipher(false)?
+            .ok_or_else(|| anyhow!("The vault key is missing"))?;
+        let nonce = hex::decode(&sealed.nonce)?;
+        if nonce.len() != 12 {
+            return Err(anyhow!("The vault is corrupted"));
+        }
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                hex::decode(&sealed.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| anyhow!("The vault cannot be decrypted with the stored key"))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn write(&self, secrets: &HashMap<String, HashMap<String, String>>) -> Result<()> {
+        let cipher = self
+            .cipher(true)?
+            .context("Failed to create the vault key")?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, serde_json::to_vec(secrets)?.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt the vault"))?;
+        let sealed = SealedVault {
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        write_atomic(&self.path, &serde_json::to_vec(&sealed)?)?;
+        trace!("Wrote {}", self.path.to_string_lossy());
+        Ok(())
+    }
+
+    /// Secrets stored for a server.
+    pub fn get(&self, server_id: &str) -> Result<HashMap<String, String>> {
+        Ok(self.read()?.remove(server_id).unwrap_or_default())
+    }
+
+    /// Replaces the secrets stored for a server; an empty map removes them.
+    pub fn set(&self, server_id: &str, secrets: HashMap<String, String>) -> Result<()> {
+        let mut all = self.read()?;
+        if secrets.is_empty() {
+            if all.remove(server_id).is_none() {
+                return Ok(());
+            }
+        } else {
+            all.insert(server_id.to_string(), secrets);
+        }
+        self.write(&all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn uses_key_file_when_named() {
+        let backend = KeyBackend::from_key_file(Some("/tmp/vault.key".into()));
+        assert!(
+            matches!(backend, KeyBackend::File(path) if path == std::path::Path::new("/tmp/vault.key"))
+        );
+        assert!(matches!(
+            KeyBackend::from_key_file(None),
+            KeyBackend::Keyring
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::with_key_file(dir.path());
+        vault.set("server", secrets(&[("TOKEN", "value")])).unwrap();
+        let mode = fs::metadata(dir.path().join("vault.key"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn round_trips_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::with_key_file(dir.path());
+        assert!(vault.get("server").unwrap().is_empty());
+
+        vault
+            .set("server", secrets(&[("TOKEN", "s3cr3t-value")]))
+            .unwrap();
+        vault
+            .set("other", secrets(&[("KEY", "other-value")]))
+            .unwrap();
+        assert_eq!(
+            vault.get("server").unwrap(),
+            secrets(&[("TOKEN", "s3cr3t-value")])
+        );
+        let on_disk = fs::read_to_string(dir.path().join(VAULT_FILENAME)).unwrap();
+        assert!(!on_disk.contains("s3cr3t-value"));
+
+        vault.set("server", HashMap::new()).unwrap();
+        assert!(vault.get("server").unwrap().is_empty());
+        assert_eq!(
+            vault.get("other").unwrap(),
+            secrets(&[("KEY", "other-value")])
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::with_key_file(dir.path());
+        vault.set("server", secrets(&[("TOKEN", "value")])).unwrap();
+
+        fs::write(dir.path().join("vault.key"), hex::encode([7u8; 32])).unwrap();
+        let error = vault.get("server").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The vault cannot be decrypted with the stored key"
+        );
+
+        fs::remove_file(dir.path().join("vault.key")).unwrap();
+        let error = vault.get("server").unwrap_err();
+        assert_eq!(error.to_string(), "The vault key is missing");
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A `major.minor.patch` version parsed leniently from tool output, release
+/// tags and registry fields: prefixes such as `v` or `bun-v`, surrounding text
+/// such as `uv 0.5.5 (abc 2024-11-28)` and missing minor or patch components
+/// are accepted and build suffixes are ignored. Pre-releases, spelled the
+/// SemVer way (`1.2.3-beta.1`) or the PEP 440 way (`1.2.3rc1`), order before
+/// their release.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers; empty for a release.
+    pub pre: Vec<Identifier>,
+}
+
+/// One dot-separated part of a pre-release. Numeric parts order numerically
+/// and before alphanumeric ones, as in SemVer.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+
+    /// Parses the first version-looking token in `text`.
+    pub fn parse(text: &str) -> Option<Self> {
+        text.split(|c: char| c.is_whitespace() || c == '@' || c == '=')
+            .find_map(Self::parse_token)
+    }
+
+    fn parse_token(token: &str) -> Option<Self> {
+        let token = token.trim_start_matches(|c: char| !c.is_ascii_digit());
+        let token = token.split('+').next()?;
+        let (core, pre) = match token.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (token, None),
+        };
+
+        let mut numbers = [0; 3];
+        let mut suffix = None;
+        for (i, part) in core.split('.').take(3).enumerate() {
+            let digits = part
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(part.len());
+            numbers[i] = part[..digits].parse().ok()?;
+            if digits < part.len() {
+                // Only the last component may carry a PEP 440 pre-release.
+                suffix = Some(&part[digits..]);
+                break;
+            }
+        }
+
+        let pre = match (pre, suffix) {
+            (Some(_), Some(_)) => return None,
+            (Some(pre), None) => parse_semver_pre(pre)?,
+            (None, Some(suffix)) => parse_pep440_pre(suffix)?,
+            (None, None) => Vec::new(),
+        };
+        Some(Version {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            pre,
+        })
+    }
+}
+
+fn parse_semver_pre(pre: &str) -> Option<Vec<Identifier>> {
+    pre.split('.')
+        .map(|part| match part.parse() {
+            Ok(number) => Some(Identifier::Numeric(number)),
+            Err(_) if !part.is_empty() => Some(Identifier::Alpha(part.to_string())),
+            Err(_) => None,
+        })
+        .collect()
+}
+
+/// Parses `a1`, `beta.2`, `rc1` and the other PEP 440 pre-release spellings
+/// into their SemVer equivalents, so `1.0.0rc1` and `1.0.0-rc.1` are equal.
+fn parse_pep440_pre(suffix: &str) -> Option<Vec<Identifier>> {
+    let suffix = suffix.trim_start_matches(['.', '_']).to_ascii_lowercase();
+    let digits = suffix
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(suffix.len());
+    let label = match suffix[..digits].trim_end_matches(['.', '_']) {
+        "a" | "alpha" => "alpha",
+        "b" | "beta" => "beta",
+        "c" | "rc" | "pre" | "preview" => "rc",
+        _ => return None,
+    };
+    let number = match &suffix[digits..] {
+        "" => 0,
+        number => number.parse().ok()?,
+    };
+    Some(vec![
+        Identifier::Alpha(label.to_string()),
+        Identifier::Numeric(number),
+    ])
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Numeric(number) => write!(f, "{}", number),
+            Identifier::Alpha(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        for (i, identifier) in self.pre.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { '-' } else { '.' }, identifier)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_semver_pre_releases() {
+        let version = parse("v1.2.3-beta.1+build.5");
+        assert_eq!(
+            version.pre,
+            vec![
+                Identifier::Alpha("beta".to_string()),
+                Identifier::Numeric(1)
+            ]
+        );
+        assert_eq!(version.to_string(), "1.2.3-beta.1");
+        assert!(parse("1.2.3-beta.1") < parse("1.2.3"));
+        assert!(parse("1.2.3-beta.1") > parse("1.2.2"));
+        assert!(parse("1.2.3-alpha") < parse("1.2.3-alpha.1"));
+        assert!(parse("1.2.3-alpha.1") < parse("1.2.3-beta"));
+        assert!(parse("1.2.3-beta.2") < parse("1.2.3-beta.11"));
+        assert!(parse("1.2.3-rc.1") < parse("1.2.3"));
+    }
+
+    #[test]
+    fn parses_pep440_pre_releases() {
+        assert_eq!(parse("1.2.3rc1"), parse("1.2.3-rc.1"));
+        assert_eq!(parse("2.0b2"), parse("2.0.0-beta.2"));
+        assert!(parse("1.2.3a1") < parse("1.2.3b1"));
+        assert!(parse("1.2.3b1") < parse("1.2.3rc1"));
+        assert!(parse("1.2.3rc1") < parse("1.2.3rc2"));
+        assert!(parse("1.2.3rc2") < parse("1.2.3"));
+        assert_eq!(Version::parse("1.2.3xyz"), None);
+    }
+
+    #[test]
+    fn parses_releases_leniently() {
+        assert_eq!(parse("uv 0.5.5 (abc 2024-11-28)"), Version::new(0, 5, 5));
+        assert_eq!(parse("bun-v1.1"), Version::new(1, 1, 0));
+        assert_eq!(parse("mcp-server==0.6.2"), Version::new(0, 6, 2));
+    }
+}
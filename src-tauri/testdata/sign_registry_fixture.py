@@ -0,0 +1,48 @@
+#!/usr/bin/env python3
+"""Regenerates the registry signature fixtures in src/api/dependency/registry.rs.
+
+TEST ONLY: the key pairs below come from fixed seeds so the output is
+reproducible. Never trust these keys for a real registry.
+
+Needs the `cryptography` package. Prints TEST_PUBKEY, OTHER_PUBKEY and
+TEST_SIGNATURE (minisign format, Ed25519 over the BLAKE2b-512 of the body).
+"""
+import base64
+import hashlib
+
+from cryptography.hazmat.primitives import serialization
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+
+TEST_SEED = bytes(range(1, 33))
+TEST_KEY_ID = bytes(range(1, 9))
+OTHER_SEED = bytes(range(33, 65))
+OTHER_KEY_ID = bytes(range(0x11, 0x19))
+
+BODY = b'[{"id":"filesystem","title":"Filesystem"}]'
+TRUSTED_COMMENT = b"timestamp:1700000000\tfile:servers.json"
+
+
+def keypair(seed, key_id):
+    secret = Ed25519PrivateKey.from_private_bytes(seed)
+    public = secret.public_key().public_bytes(
+        serialization.Encoding.Raw, serialization.PublicFormat.Raw
+    )
+    return secret, base64.b64encode(b"Ed" + key_id + public).decode()
+
+
+def main():
+    secret, test_pubkey = keypair(TEST_SEED, TEST_KEY_ID)
+    _, other_pubkey = keypair(OTHER_SEED, OTHER_KEY_ID)
+    signature = secret.sign(hashlib.blake2b(BODY, digest_size=64).digest())
+    global_signature = secret.sign(signature + TRUSTED_COMMENT)
+    print("TEST_PUBKEY:", test_pubkey)
+    print("OTHER_PUBKEY:", other_pubkey)
+    print("TEST_SIGNATURE:")
+    print("untrusted comment: signature from minisign secret key")
+    print(base64.b64encode(b"ED" + TEST_KEY_ID + signature).decode())
+    print("trusted comment:", TRUSTED_COMMENT.decode())
+    print(base64.b64encode(global_signature).decode())
+
+
+if __name__ == "__main__":
+    main()
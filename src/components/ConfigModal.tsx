@@ -2,7 +2,7 @@ import { Dialog, DialogContent, DialogFooter, DialogHeader, DialogTitle } from "
 import { Input } from "@/components/ui/input";
 import { open as pathSelect } from '@tauri-apps/plugin-dialog';
 import { Label } from "@/components/ui/label";
-import type { InputArg } from "@/types/server";
+import type { EnvVarSpec, FieldError, InputArg } from "@/types/server";
 import { open as urlOpen } from '@tauri-apps/plugin-shell';
 import { useState } from 'react';
 import ReactMarkdown from 'react-markdown';
@@ -13,13 +13,18 @@ interface ConfigModalProps {
     isOpen: boolean
     onClose: () => void
     env: Record<string, string>
+    envSchema: EnvVarSpec[]
+    errors: FieldError[]
     guide: string
     inputArg: InputArg
     onSave: (config: Record<string, string>, args: string[]) => void
 }
 
-export function ConfigModal({ isOpen, onClose, env, guide, inputArg, onSave }: ConfigModalProps) {
+export function ConfigModal({ isOpen, onClose, env, envSchema, errors, guide, inputArg, onSave }: ConfigModalProps) {
     const [config, setConfig] = useState<Record<string, string>>({})
+    const envKeys = [...new Set([...Object.keys(env), ...envSchema.map((spec) => spec.name)])]
+    const specFor = (key: string) => envSchema.find((spec) => spec.name === key)
+    const errorFor = (key: string) => errors.find((error) => error.field === key)?.message
 
     const handleInputChange = (key: string, value: string) => {
         setConfig(prev => ({ ...prev, [key]: value }))
@@ -94,17 +99,25 @@ export function ConfigModal({ isOpen, onClose, env, guide, inputArg, onSave }: C
                 </DialogHeader>
                 <div className="px-6 py-4 border-y">
                     <div className="space-y-4">
-                        {Object.keys(env).map((key) => (
+                        {envKeys.map((key) => (
                             <div key={key} className="flex flex-col space-y-2">
                                 <Label htmlFor={key} className="font-medium">
                                     {key}
+                                    {specFor(key)?.required && <span className="text-destructive"> *</span>}
                                 </Label>
                                 <Input
                                     id={key}
-                                    placeholder={`Enter your ${key.toLowerCase()}`}
+                                    type={specFor(key)?.secret ? 'password' : 'text'}
+                                    placeholder={specFor(key)?.default ?? `Enter your ${key.toLowerCase()}`}
                                     value={config[key] || env[key] || ''}
                                     onChange={(e) => handleInputChange(key, e.target.value)}
                                 />
+                                {specFor(key)?.description && (
+                                    <p className="text-sm text-muted-foreground">{specFor(key)?.description}</p>
+                                )}
+                                {errorFor(key) && (
+                                    <p className="text-sm text-destructive">{errorFor(key)}</p>
+                                )}
                             </div>
                         ))}
                         {
@@ -2,8 +2,9 @@ import { Tag } from "@/components/Tag"
 import { Avatar, AvatarFallback, AvatarImage } from "@/components/ui/avatar"
 import { Button } from "@/components/ui/button"
 import { Card, CardContent } from "@/components/ui/card"
-import type { InstallStatus, ServerCardData } from '@/types/server'
+import type { FieldError, InstallReport, InstallStatus, MagnetError, ServerCardData } from '@/types/server'
 import { getRelativeTime } from '@/utils/getRelativeTime'
+import { toMagnetError } from '@/utils/toMagnetError'
 import { invoke } from "@tauri-apps/api/core"
 import { motion } from 'framer-motion'
 import { Check, Download, Loader2, Settings, Star } from 'lucide-react'
@@ -23,32 +24,63 @@ export function ServerCard({
     tags,
     isInstalled,
     env,
+    envSchema,
     guide,
     inputArg,
 }: ServerCardProps) {
     const [isHovered, setIsHovered] = useState(false)
     const [isConfigModalOpen, setIsConfigModalOpen] = useState(false)
     const [installStatus, setInstallStatus] = useState<InstallStatus>(isInstalled ? 'installed' : 'install')
+    const [error, setError] = useState<MagnetError | null>(null)
+    const [warnings, setWarnings] = useState<FieldError[]>([])
+    const hasSettings = Object.keys(env).length > 0 || envSchema.length > 0 || !!inputArg.name
+    // Servers whose schema requires a value cannot be installed without the form.
+    const canQuickInstall = Object.keys(env).length === 0 && !inputArg.name && !envSchema.some((spec) => spec.required)
     const relativeTime = getRelativeTime(publishDate)
 
+    // Runs a command with the card busy, landing on `done` when it succeeds and
+    // back on the current status when it fails.
+    const run = async (busy: InstallStatus, done: InstallStatus, command: () => Promise<FieldError[]>) => {
+        let next = installStatus
+        setInstallStatus(busy)
+        setError(null)
+        setWarnings([])
+        try {
+            setWarnings(await command())
+            next = done
+        } catch (e) {
+            const magnetError = toMagnetError(e)
+            setError(magnetError)
+            // Let the user fix the rejected values where they enter them.
+            if (magnetError.code === 'VALIDATION') {
+                setIsConfigModalOpen(true)
+            }
+        } finally {
+            setInstallStatus(next)
+        }
+    }
+
     const handleConfigSave = async (config: Record<string, string>, args: string[]) => {
-        console.log('Saved config:', config)
-        setInstallStatus('installing')
-        await invoke('update_server', { serverId: id, env: config , inputArg: args})
-        setInstallStatus('installed')
+        await run('installing', 'installed', async () => {
+            const report = await invoke<InstallReport>('update_server', { serverId: id, env: config, inputArg: args })
+            return report.warnings
+        })
     }
 
     const handleInstall = async () => {
         if (installStatus === 'installed') {
-            await invoke('uninstall_server', { serverId: id });
-            setInstallStatus('install');
-            return;
+            await run('uninstall', 'install', async () => {
+                await invoke<void>('uninstall_server', { serverId: id })
+                return []
+            })
+            return
         }
 
-        if (Object.keys(env).length === 0 && !inputArg.name) {
-            setInstallStatus('installing');
-            await invoke('install_server', { serverId: id });
-            setInstallStatus('installed');
+        if (canQuickInstall) {
+            await run('installing', 'installed', async () => {
+                const report = await invoke<InstallReport>('install_server', { serverId: id })
+                return report.warnings
+            })
         } else {
             setIsConfigModalOpen(true);
         }
@@ -70,6 +102,13 @@ export function ServerCard({
                         Installing...
                     </>
                 )
+            case 'uninstall':
+                return (
+                    <>
+                        <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+                        Uninstalling...
+                    </>
+                )
             case 'installed':
                 return isHovered ? (
                     <>
@@ -105,7 +144,7 @@ export function ServerCard({
                             <h3 className="font-semibold text-base leading-none mb-1">{title}</h3>
                             <p className="text-sm text-muted-foreground">{creator}</p>
                         </div>
-                        {installStatus === 'installed' && (Object.keys(env).length > 0 || inputArg.name) && (
+                        {installStatus === 'installed' && hasSettings && (
                             <Button
                                 variant="outline"
                                 size="icon"
@@ -146,16 +185,37 @@ export function ServerCard({
                         className="w-full"
                         variant={installStatus === 'installed' ? 'secondary' : 'default'}
                         onClick={handleInstall}
-                        disabled={installStatus === 'installing'}
+                        disabled={installStatus === 'installing' || installStatus === 'uninstall'}
                     >
                         {getButtonContent()}
                     </Button>
+                    {error && (
+                        <div className="mt-2 text-xs text-destructive">
+                            <p>{error.message}</p>
+                            {error.fields && error.fields.length > 0 && (
+                                <ul className="list-disc pl-4">
+                                    {error.fields.map((field) => (
+                                        <li key={field.field}>{field.field}: {field.message}</li>
+                                    ))}
+                                </ul>
+                            )}
+                        </div>
+                    )}
+                    {warnings.length > 0 && (
+                        <ul className="mt-2 list-disc pl-4 text-xs text-yellow-600 dark:text-yellow-400">
+                            {warnings.map((warning) => (
+                                <li key={warning.field}>{warning.field}: {warning.message}</li>
+                            ))}
+                        </ul>
+                    )}
                 </CardContent>
             </Card>
             <ConfigModal
                 isOpen={isConfigModalOpen}
                 onClose={() => setIsConfigModalOpen(false)}
                 env={env}
+                envSchema={envSchema}
+                errors={error?.fields ?? []}
                 guide={guide}
                 inputArg={inputArg}
                 onSave={handleConfigSave}
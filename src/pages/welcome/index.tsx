@@ -3,9 +3,18 @@ import { invoke } from "@tauri-apps/api/core";
 import { debug } from "@tauri-apps/plugin-log";
 import { useEffect, useState } from "react";
 
+type RuntimeStatus = {
+  installed: boolean;
+  path: string | null;
+  source: "system" | "managed" | null;
+  version: string | null;
+  minimumVersion: string;
+  problems: string[];
+};
+
 type DependencyStatus = {
-  uv: boolean;
-  node: boolean;
+  uv: RuntimeStatus;
+  node: RuntimeStatus;
 };
 
 export default function WelcomePage() {
@@ -20,8 +29,8 @@ export default function WelcomePage() {
     setIsChecking(true);
     try {
       const status = await invoke<DependencyStatus>("check_dependency");
-      setNpmInstalled(status.node);
-      setUvInstalled(status.uv);
+      setNpmInstalled(status.node.installed);
+      setUvInstalled(status.uv.installed);
       debug("Start check_resource");
       const resourceLoaded = await invoke<boolean>("check_resource");
       debug("End check_resource");
@@ -6,6 +6,18 @@ export interface InputArg {
   value: string[]
 }
 
+// Describes one env var a server reads.
+export interface EnvVarSpec {
+  name: string
+  description: string
+  required: boolean
+  secret: boolean
+  pattern?: string
+  enum?: string[]
+  default?: string
+  docsUrl?: string
+}
+
 interface BaseServerCardData {
   id: string
   title: string
@@ -16,6 +28,7 @@ interface BaseServerCardData {
   tags: string[]
   isInstalled: boolean,
   env: Record<string, string>
+  envSchema: EnvVarSpec[]
   guide: string
   inputArg: InputArg
 }
@@ -28,3 +41,19 @@ export interface RawServerCardData extends BaseServerCardData {
   publishDate: string;
 }
 export type InstallStatus = 'install' | 'installing' | 'installed' | 'uninstall'
+
+export interface FieldError {
+  field: string
+  message: string
+}
+
+// What the backend commands reject with.
+export interface MagnetError {
+  code: string
+  message: string
+  fields?: FieldError[]
+}
+
+export interface InstallReport {
+  warnings: FieldError[]
+}
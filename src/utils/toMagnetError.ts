@@ -0,0 +1,9 @@
+import type { MagnetError } from '@/types/server'
+
+export function toMagnetError(error: unknown): MagnetError {
+  if (typeof error === 'object' && error !== null && 'message' in error) {
+    const { code, message, fields } = error as Partial<MagnetError>;
+    return { code: code ?? 'unknown', message: String(message), fields };
+  }
+  return { code: 'unknown', message: String(error) };
+}